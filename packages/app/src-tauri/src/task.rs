@@ -1,4 +1,4 @@
-use oics::omr;
+use oics::omr::{self, CorrectionOptions};
 use serde::Serialize;
 
 use crate::thread_pool;
@@ -13,6 +13,10 @@ struct TaskCompletedEventPayload {
     task_id: usize,
     output_path: String,
     result: String,
+    // 置信度，取值范围为 [0, 1]
+    confidence: Option<f64>,
+    // 旋转角度的估计误差（±deg）
+    uncertainty: Option<f64>,
 }
 
 #[tauri::command]
@@ -20,14 +24,12 @@ pub fn add_task(
     task_id: usize,
     input_file: String,
     output_file: String,
-    projection_max_angle: u16,
-    projection_angle_step: f64,
-    projection_max_width: i32,
-    projection_max_height: i32,
-    hough_min_line_length: f64,
-    hough_max_line_gap: f64,
+    options: CorrectionOptions,
     window: tauri::Window,
-) {
+) -> Result<(), String> {
+    // 参数不合法时直接返回错误信息，不加入任务队列
+    options.validate().map_err(|error| error.to_string())?;
+
     thread_pool::request_task(move || {
         window
             .emit(
@@ -35,34 +37,31 @@ pub fn add_task(
                 StartRunningTaskEventPayload { task_id },
             )
             .unwrap();
-        let result = omr::correct_default(
-            &input_file,
-            &output_file,
-            projection_max_angle,
-            projection_angle_step,
-            projection_max_width,
-            projection_max_height,
-            hough_min_line_length,
-            hough_max_line_gap,
-        );
+        let result = omr::correct_default(&input_file, &output_file, &options);
         let task_completed_payload: TaskCompletedEventPayload = match result {
-            Ok((_rotate_angle, is_debatable)) => TaskCompletedEventPayload {
+            Ok(correction_result) => TaskCompletedEventPayload {
                 task_id,
-                result: String::from(if is_debatable {
+                result: String::from(if correction_result.need_check {
                     "debatable"
                 } else {
                     "finished"
                 }),
                 output_path: output_file,
+                confidence: Some(correction_result.confidence),
+                uncertainty: Some(correction_result.uncertainty),
             },
             Err(_) => TaskCompletedEventPayload {
                 task_id,
                 result: String::from("error"),
                 output_path: output_file,
+                confidence: None,
+                uncertainty: None,
             },
         };
         window
             .emit("task_completed", task_completed_payload)
             .unwrap();
     });
+
+    Ok(())
 }
@@ -3,178 +3,30 @@ use oics::{
     core::{self, Scalar},
     imgcodecs,
     imgproc,
+    omr::CorrectionOptions,
     // prelude::MatTraitConstManual,
     transfer::{self, TransformableMatrix},
-    types::{ImageFormat, RotateClipStrategy},
+    types::{ImageFormat, PeakInterpolation, RotateClipStrategy},
 };
 use rand::Rng;
 use serde::Serialize;
-use std::{
-    collections::HashMap,
-    path::Path,
-    sync::{Arc, Mutex},
-    thread,
-    time::Instant,
-};
-
-fn get_most_possible_angle(vec: (Vec<f64>, Vec<f64>)) -> usize {
-    // 处理垂直投影数据
-    let vertical_vec = vec.0;
-    let mut vertical_possibles: (f64, Vec<usize>) = (vertical_vec[0], vec![0]);
-    for (index, val) in vertical_vec.iter().enumerate() {
-        if *val > vertical_possibles.0 {
-            vertical_possibles.0 = *val;
-            vertical_possibles.1 = vec![index];
-        } else if *val == vertical_possibles.0 {
-            vertical_possibles.1.push(index);
-        }
-    }
-
-    // 处理水平投影数据
-    let horizontal_vec = vec.1;
-    let mut horizontal_possibles: (f64, Vec<usize>) = (horizontal_vec[0], vec![0]);
-    for (index, val) in horizontal_vec.iter().enumerate() {
-        if *val > horizontal_possibles.0 {
-            horizontal_possibles.0 = *val;
-            horizontal_possibles.1 = vec![index];
-        } else if *val == horizontal_possibles.0 {
-            horizontal_possibles.1.push(index);
-        }
-    }
-
-    // 唯一结果且相等
-    if vertical_possibles.1.len() == 1
-        && horizontal_possibles.1.len() == 1
-        && vertical_possibles.1[0] == horizontal_possibles.1[0]
-    {
-        return vertical_possibles.1[0];
-    }
-
-    let mut candidate_hashmap = HashMap::new();
-    for deg_index in vertical_possibles.1 {
-        candidate_hashmap.entry(deg_index).or_insert((
-            vertical_vec[deg_index as usize],
-            horizontal_vec[deg_index as usize],
-        ));
-    }
-    for deg_index in horizontal_possibles.1 {
-        candidate_hashmap.entry(deg_index).or_insert((
-            vertical_vec[deg_index as usize],
-            horizontal_vec[deg_index as usize],
-        ));
-    }
-
-    let mut sdp = 0.0;
-    let mut most_possible_angle: Option<usize> = None;
-    for (angle, (vsd, hsd)) in candidate_hashmap {
-        let cur_sdp = vsd.powf(2.0) + hsd.powf(2.0);
-        if sdp < cur_sdp {
-            sdp = cur_sdp;
-            most_possible_angle = Some(angle);
-        }
-    }
-
-    match most_possible_angle {
-        Some(value) => value,
-        None => vertical_vec.len() / 2,
-    }
-}
+use std::{path::Path, thread, time::Instant};
 
+/// 在二值图上查找偏转角，结果与线程数无关
 fn find_target_angle(
     max_angle: u16,
     step: f64,
     thresh_image: TransformableMatrix,
     threads: usize,
-) -> f64 {
-    let max_angle = (max_angle as f64 / step) as u16;
-    let min_angle = -(max_angle as i32);
-    let range = min_angle..(max_angle as i32);
-
-    let standard_deviations = if threads <= 1 {
-        // 单线程
-        let mut standard_deviations = (
-            Vec::with_capacity(range.len()),
-            Vec::with_capacity(range.len()),
-        );
-
-        for deg in range {
-            let rotated_image = transfer::rotate_mat(
-                &thresh_image,
-                deg as f64 * step,
-                1.0,
-                imgproc::WARP_POLAR_LINEAR,
-                core::BORDER_CONSTANT,
-                Scalar::new(255.0, 255.0, 255.0, 0.0), // b g r
-                RotateClipStrategy::DEFAULT,
-            )
-            .expect("旋转图像时发生错误！");
-
-            let projection_standard_deviations =
-                transfer::get_projection_standard_deviations(&rotated_image)
-                    .expect("计算投影标准差时发生错误");
-
-            standard_deviations.0.push(projection_standard_deviations.0);
-            standard_deviations.1.push(projection_standard_deviations.1);
-        }
-
-        standard_deviations
-    } else {
-        // 多线程
-        let mut handles = Vec::with_capacity(threads);
-        let index = Arc::new(Mutex::new(0));
-
-        let arc_standard_deviations =
-            Arc::new(Mutex::new((vec![0.0; range.len()], vec![0.0; range.len()])));
-        let arc_thresh_image = Arc::new(thresh_image);
-        for _ in 0..threads {
-            let ref_standard_deviations = Arc::clone(&arc_standard_deviations);
-            let ref_index = Arc::clone(&index);
-            let ref_thresh_image = Arc::clone(&arc_thresh_image);
-
-            let handle = thread::spawn(move || loop {
-                let angle = {
-                    let mut index_locker = ref_index.lock().unwrap();
-                    let current_index = *index_locker;
-                    if current_index == (max_angle * 2) as i32 {
-                        break;
-                    }
-                    *index_locker = current_index + 1;
-                    current_index + min_angle
-                };
-
-                let rotated_image = transfer::rotate_mat(
-                    &ref_thresh_image,
-                    angle as f64,
-                    1.0,
-                    imgproc::WARP_POLAR_LINEAR,
-                    core::BORDER_CONSTANT,
-                    Scalar::new(255.0, 255.0, 255.0, 0.0), // b g r
-                    RotateClipStrategy::DEFAULT,
-                )
-                .expect("旋转图像时发生错误！");
-
-                let projection_standard_deviations =
-                    transfer::get_projection_standard_deviations(&rotated_image)
-                        .expect("计算投影标准差时发生错误");
-
-                {
-                    let mut rsd = ref_standard_deviations.lock().unwrap();
-                    rsd.0[(angle - min_angle) as usize] = projection_standard_deviations.0;
-                    rsd.1[(angle - min_angle) as usize] = projection_standard_deviations.1;
-                }
-            });
-            handles.push(handle);
-        }
-
-        for handle in handles {
-            handle.join().unwrap();
-        }
-
-        let sd = arc_standard_deviations.lock().unwrap().to_owned();
-        sd
-    };
-
-    return ((get_most_possible_angle(standard_deviations) as f64) - (max_angle as f64)) * step;
+    peak_interpolation: PeakInterpolation,
+) -> oics::Result<f64> {
+    oics::projection::get_angle_from_thresh_binary(
+        &thresh_image,
+        max_angle,
+        step,
+        threads,
+        peak_interpolation,
+    )
 }
 
 #[allow(dead_code)]
@@ -216,6 +68,12 @@ enum TestProcessMethod {
     Hough,
     FFT,
 }
+#[derive(Serialize, Clone)]
+struct TestErrorPayload {
+    test_id: usize,
+    message: String,
+}
+
 #[allow(dead_code)]
 #[derive(Serialize, Clone)]
 struct TestProcessPayload {
@@ -236,18 +94,15 @@ const OUTPUT_DIR_PATH: &str = "./resources/test/result";
 #[tauri::command]
 pub fn run_test(
     test_id: usize,
-    projection_max_angle: u16,
-    projection_angle_step: f64,
-    projection_max_width: i32,
-    projection_max_height: i32,
-    hough_min_line_length: f64,
-    hough_max_line_gap: f64,
+    options: CorrectionOptions,
     fft_canny_threshold_lower: f64,
     fft_canny_threshold_higher: f64,
     fft_min_line_length: f64,
     fft_max_line_gap: f64,
     window: tauri::Window,
-) -> usize {
+) -> Result<usize, String> {
+    options.validate().map_err(|error| error.to_string())?;
+
     thread::spawn(move || {
         let instant = Instant::now();
         let mut random = rand::thread_rng();
@@ -310,14 +165,34 @@ pub fn run_test(
                 let mut gray_image =
                     transfer::transfer_rgb_image_to_gray_image(&original_image).unwrap();
                 let gray_image = gray_image
-                    .shrink_to(projection_max_width, projection_max_height)
+                    .shrink_to(options.projection_max_width, options.projection_max_height)
                     .unwrap();
 
                 transfer::transfer_gray_image_to_thresh_binary(&gray_image).unwrap()
             };
 
-            let projection_angle =
-                find_target_angle(projection_max_angle, projection_angle_step, thresh_image, 1);
+            let projection_angle = match find_target_angle(
+                options.projection_max_angle,
+                options.projection_angle_step,
+                thresh_image,
+                1,
+                options.projection_peak_interpolation,
+            ) {
+                Ok(angle) => angle,
+                // 测试已在后台线程中运行，通过事件通知前端结束测试
+                Err(error) => {
+                    window
+                        .emit(
+                            "run_test_error",
+                            TestErrorPayload {
+                                test_id,
+                                message: error.to_string(),
+                            },
+                        )
+                        .unwrap();
+                    return;
+                }
+            };
 
             let final_image = transfer::rotate_mat(
                 &original_image,
@@ -380,14 +255,18 @@ pub fn run_test(
             let hough_start = instant.elapsed().as_millis();
             // let min_line_length = original_image.get_mat().size().unwrap().width as f64 * 0.1;
             // let max_line_gap = min_line_length * 0.1;
-            let hough_angle = oics::hough::get_angle_with_hough(
+            let hough_angle = match oics::hough::get_angle_with_hough(
                 &transfer::transfer_rgb_image_to_gray_image(&original_image).unwrap(),
-                hough_min_line_length,
-                hough_max_line_gap,
+                options.hough_min_line_length,
+                options.hough_max_line_gap,
                 &file_name,
                 &(String::from(OUTPUT_DIR_PATH) + &"/edges/"),
-            )
-            .unwrap();
+            ) {
+                Ok(angle) => angle,
+                // 未检测到直线时不旋转
+                Err(oics::Error::NoFeatures(_)) => 0.0,
+                Err(error) => panic!("{}", error),
+            };
             transfer::rotate_mat(
                 &original_image,
                 hough_angle,
@@ -447,7 +326,7 @@ pub fn run_test(
             let fft_start = instant.elapsed().as_millis();
 
             let gray_image = transfer::transfer_rgb_image_to_gray_image(&original_image).unwrap();
-            let fft_angle = oics::fft::get_angle_with_fft(
+            let fft_angle = match oics::fft::get_angle_with_fft(
                 &gray_image,
                 fft_canny_threshold_lower,
                 fft_canny_threshold_higher,
@@ -455,8 +334,12 @@ pub fn run_test(
                 fft_max_line_gap,
                 &file_name,
                 &(String::from(OUTPUT_DIR_PATH) + &"/fft_lined/"),
-            )
-            .unwrap();
+            ) {
+                Ok(angle) => angle,
+                // 未检测到直线时不旋转
+                Err(oics::Error::NoFeatures(_)) => 0.0,
+                Err(error) => panic!("{}", error),
+            };
             transfer::rotate_mat(
                 &original_image,
                 fft_angle,
@@ -515,10 +398,12 @@ pub fn run_test(
 
         // 误差测试
         let projection_mistake_result = TestMistakeResult {
-            arithmetic_mean: oics::calculate::get_arithmetic_mean(&projection_deviation_array),
+            arithmetic_mean: oics::calculate::get_arithmetic_mean(&projection_deviation_array)
+                .unwrap(),
             standard_deviation: oics::calculate::get_standard_deviation(
                 &projection_deviation_array,
-            ),
+            )
+            .unwrap(),
             max_mistake: {
                 let mut largest = projection_deviation_array[0];
                 for val in projection_deviation_array {
@@ -531,8 +416,9 @@ pub fn run_test(
             },
         };
         let hough_mistake_result = TestMistakeResult {
-            arithmetic_mean: oics::calculate::get_arithmetic_mean(&hough_deviation_array),
-            standard_deviation: oics::calculate::get_standard_deviation(&hough_deviation_array),
+            arithmetic_mean: oics::calculate::get_arithmetic_mean(&hough_deviation_array).unwrap(),
+            standard_deviation: oics::calculate::get_standard_deviation(&hough_deviation_array)
+                .unwrap(),
             max_mistake: {
                 let mut largest = hough_deviation_array[0];
                 for val in hough_deviation_array {
@@ -545,8 +431,9 @@ pub fn run_test(
             },
         };
         let fft_mistake_result = TestMistakeResult {
-            arithmetic_mean: oics::calculate::get_arithmetic_mean(&fft_deviation_array),
-            standard_deviation: oics::calculate::get_standard_deviation(&fft_deviation_array),
+            arithmetic_mean: oics::calculate::get_arithmetic_mean(&fft_deviation_array).unwrap(),
+            standard_deviation: oics::calculate::get_standard_deviation(&fft_deviation_array)
+                .unwrap(),
             max_mistake: {
                 let mut largest = fft_deviation_array[0];
                 for val in fft_deviation_array {
@@ -576,5 +463,5 @@ pub fn run_test(
         window.emit("test_result", result).unwrap();
     });
 
-    test_id
+    Ok(test_id)
 }
@@ -60,6 +60,20 @@ const getExePath = async (): Promise<string> => {
 	return exePath.replace(/^(\\\\\?\\)(.*?)/, '$2');
 };
 
+/**
+ * 将纠偏参数转换为 `oics::omr::CorrectionOptions`
+ * @param omrConfig 纠偏参数
+ * @returns
+ */
+const getCorrectionOptions = (omrConfig: Omit<ITaskProps['omrConfig'], 'outputDir'>) => ({
+	projection_max_angle: omrConfig.projectionMaxAngle,
+	projection_angle_step: omrConfig.projectionAngleStep,
+	projection_max_width: omrConfig.projectionMaxWidth,
+	projection_max_height: omrConfig.projectionMaxHeight,
+	hough_min_line_length: omrConfig.houghMinLineLength,
+	hough_max_line_gap: omrConfig.houghMaxLineGap,
+});
+
 const runTest = async (testId: number, params: ReturnType<typeof getLibParams>) => {
 	if (Paths.exePath === undefined) {
 		await Paths.initPaths();
@@ -75,7 +89,11 @@ const runTest = async (testId: number, params: ReturnType<typeof getLibParams>)
 
 	return invoke('run_test', {
 		testId,
-		...params,
+		options: getCorrectionOptions(params),
+		fftCannyThresholdLower: params.fftCannyThresholdLower,
+		fftCannyThresholdHigher: params.fftCannyThresholdHigher,
+		fftMinLineLength: params.fftMinLineLength,
+		fftMaxLineGap: params.fftMaxLineGap,
 	});
 };
 
@@ -97,16 +115,11 @@ const addTask = async (taskProps: ITaskProps) => {
 	const fileExt = await tauriPath.extname(taskProps.src);
 	const fileName = await tauriPath.basename(taskProps.src, `.${fileExt}`);
 
-	invoke('add_task', {
+	return invoke('add_task', {
 		taskId: taskProps.id,
 		inputFile: taskProps.src,
 		outputFile: path.resolveSync(taskProps.omrConfig.outputDir, `${fileName}.jpg`),
-		projectionMaxAngle: taskProps.omrConfig.projectionMaxAngle,
-		projectionAngleStep: taskProps.omrConfig.projectionAngleStep,
-		projectionMaxWidth: taskProps.omrConfig.projectionMaxWidth,
-		projectionMaxHeight: taskProps.omrConfig.projectionMaxHeight,
-		houghMinLineLength: taskProps.omrConfig.houghMinLineLength,
-		houghMaxLineGap: taskProps.omrConfig.houghMaxLineGap,
+		options: getCorrectionOptions(taskProps.omrConfig),
 	});
 };
 
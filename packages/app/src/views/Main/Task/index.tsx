@@ -62,6 +62,8 @@ export default forwardRef<ITaskRef, ITaskComponentProps>((props, ref) => {
 				task_id: number;
 				result: 'finished' | 'debatable' | 'error';
 				output_path: string;
+				confidence: number | null;
+				uncertainty: number | null;
 			};
 			if (task_id !== id) return;
 			setStatus((currentStatus) => {
@@ -82,7 +84,11 @@ export default forwardRef<ITaskRef, ITaskComponentProps>((props, ref) => {
 		setStatus((oldStatus) => {
 			if (oldStatus !== 'ready') return oldStatus;
 
-			Invokers.addTask(props);
+			// 参数校验失败时不会触发 `task_completed` 事件，需要在此处标记任务出错
+			Invokers.addTask(props).catch((error) => {
+				console.error(error);
+				setStatus('error');
+			});
 			return 'waiting';
 		});
 	}, [props]);
@@ -1,6 +1,14 @@
 import { IFftParams, IHoughParams, IProjectionParams } from '@/types';
 import { getLibParams, Invokers } from '@/utils';
-import { Button, CircularProgress, Divider, Grid, LinearProgress } from '@mui/material';
+import {
+	Alert,
+	Button,
+	CircularProgress,
+	Divider,
+	Grid,
+	LinearProgress,
+	Snackbar,
+} from '@mui/material';
 // NOTE: 这里使用 window 会报错：Error: @vitejs/plugin-react can't detect preamble. Something is wrong.
 import { event, window as tauriApiWindow } from '@tauri-apps/api';
 import { useCallback, useEffect, useState } from 'react';
@@ -17,6 +25,11 @@ type RunTestProgressEventPayload = {
 	total_count: number;
 };
 
+type RunTestErrorEventPayload = {
+	test_id: number;
+	message: string;
+};
+
 interface MethodTestProgress {
 	status: 'WAITING' | 'RUNNING' | 'PROCESSED';
 	details: RunTestProgressEventPayload;
@@ -74,6 +87,7 @@ function App() {
 	);
 
 	const [testResult, setTestResult] = useState<TestResult | null>(null);
+	const [testError, setTestError] = useState<string | null>(null);
 	useEffect(() => {
 		const unListenTestResult = event.listen('test_result', (ev) => {
 			if (ev.windowLabel !== 'test') return;
@@ -96,6 +110,20 @@ function App() {
 				currentWindow.setFocus();
 			});
 		});
+		// 测试在后台线程中出错时结束测试并提示错误
+		const unListenTestError = event.listen('run_test_error', (ev) => {
+			if (ev.windowLabel !== 'test') return;
+
+			const payload = ev.payload as RunTestErrorEventPayload;
+			setTestInfo((currentTestInfo) => {
+				if (payload.test_id !== currentTestInfo.testId) return currentTestInfo;
+
+				setTestError(payload.message);
+				return {
+					testId: 0,
+				};
+			});
+		});
 		const unListenTestProgress = event.listen('run_test_progress_event', (ev) => {
 			if (ev.windowLabel !== 'test') return;
 
@@ -137,6 +165,9 @@ function App() {
 			unListenTestResult.then((unListenFn) => {
 				unListenFn();
 			});
+			unListenTestError.then((unListenFn) => {
+				unListenFn();
+			});
 		};
 	}, []);
 
@@ -165,14 +196,36 @@ function App() {
 			},
 		});
 		setTestResult(null);
+		setTestError(null);
 		setProjectionTestProgress(createDefaultMethodProgress(newTestId, 'Projection'));
 		setHoughTestProgress(createDefaultMethodProgress(newTestId, 'Hough'));
 		setFftTestProgress(createDefaultMethodProgress(newTestId, 'FFT'));
-		Invokers.runTest(newTestId, libParams);
+		// 参数校验失败时不会触发 `test_result` 事件，需要在此处结束测试并提示错误
+		Invokers.runTest(newTestId, libParams).catch((error) => {
+			setTestInfo((currentTestInfo) =>
+				currentTestInfo.testId === newTestId ? { testId: 0 } : currentTestInfo
+			);
+			setTestError(String(error));
+		});
 	}, []);
 
 	return (
 		<div className={styles.app}>
+			<Snackbar
+				anchorOrigin={{ vertical: 'top', horizontal: 'center' }}
+				open={testError !== null}
+				autoHideDuration={4000}
+				onClose={() => setTestError(null)}
+			>
+				<Alert
+					onClose={() => setTestError(null)}
+					severity='error'
+					variant='filled'
+					sx={{ width: '100%' }}
+				>
+					{testError}
+				</Alert>
+			</Snackbar>
 			<div className={styles.content}>
 				<div className={styles.header}>
 					<Button
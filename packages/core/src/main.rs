@@ -4,9 +4,13 @@ use noise::add_gaussian_noise;
 use oics::{
     self,
     core::{self, Scalar},
+    detector::ProjectionSweepDetector,
     imgcodecs, imgproc,
+    omr::CorrectionOptions,
     transfer::{self, TransformableMatrix},
-    types::{ImageFormat, RotateClipStrategy},
+    types::{
+        BinarizationStrategy, DenoiseStrategy, ImageFormat, PeakInterpolation, RotateClipStrategy,
+    },
 };
 use rand::Rng;
 use std::{path::Path, time::Instant};
@@ -14,8 +18,17 @@ use std::{path::Path, time::Instant};
 mod noise;
 
 const DATA_SET_DIR_PATH: &str = "../../dataset/dataset";
+// 检测前的降噪方式，添加噪声后可选用中值滤波（椒盐噪声）或非局部均值降噪（高斯噪声）
+const DENOISE_STRATEGY: DenoiseStrategy = DenoiseStrategy::NONE;
 #[allow(dead_code)]
-fn run_test(p: bool, h: bool, f: bool) {
+fn run_test(
+    options: &CorrectionOptions,
+    projection_resize_scale: f64,
+    p: bool,
+    h: bool,
+    f: bool,
+    r: bool,
+) {
     let instant = Instant::now();
     let mut random = rand::thread_rng();
 
@@ -23,10 +36,12 @@ fn run_test(p: bool, h: bool, f: bool) {
     let mut projection_run_time_array: Vec<u128> = vec![];
     let mut hough_run_time_array: Vec<u128> = vec![];
     let mut fft_run_time_array: Vec<u128> = vec![];
+    let mut radon_run_time_array: Vec<u128> = vec![];
     // 准确度测试
     let mut projection_deviation_array: Vec<f64> = vec![];
     let mut hough_deviation_array: Vec<f64> = vec![];
     let mut fft_deviation_array: Vec<f64> = vec![];
+    let mut radon_deviation_array: Vec<f64> = vec![];
 
     for entry in walkdir::WalkDir::new(DATA_SET_DIR_PATH) {
         let this_entry = entry.unwrap();
@@ -64,10 +79,28 @@ fn run_test(p: bool, h: bool, f: bool) {
         //     dst
         // });
 
+        // 用于检测的降噪图像，输出图像仍使用原图
+        let detection_image = {
+            let mut image = original_image.clone();
+            image.denoise(DENOISE_STRATEGY).unwrap();
+            image
+        };
+
         if p {
             let projection_start = instant.elapsed().as_millis();
-            let projection_angle =
-                oics::projection::get_angle_with_projections(&original_image, 45, 0.2, 0.2, 1);
+            let projection_angle = oics::projection::get_angle_with_projections(
+                &detection_image,
+                &ProjectionSweepDetector {
+                    max_angle: options.projection_max_angle,
+                    step: options.projection_angle_step,
+                    resize_scale: projection_resize_scale,
+                    threads: options.projection_threads,
+                    peak_interpolation: options.projection_peak_interpolation,
+                    illumination: options.illumination_strategy,
+                    ..ProjectionSweepDetector::default()
+                },
+            )
+            .unwrap();
 
             let final_image = transfer::rotate_mat(
                 &original_image,
@@ -100,14 +133,18 @@ fn run_test(p: bool, h: bool, f: bool) {
             let hough_start = instant.elapsed().as_millis();
             // let min_line_length = original_image.get_mat().size().unwrap().width as f64 * 0.1;
             // let max_line_gap = min_line_length * 0.1;
-            let hough_angle = oics::hough::get_angle_with_hough(
-                &transfer::transfer_rgb_image_to_gray_image(&original_image).unwrap(),
-                125.0,
-                15.0,
+            let hough_angle = match oics::hough::get_angle_with_hough(
+                &transfer::transfer_rgb_image_to_gray_image(&detection_image).unwrap(),
+                options.hough_min_line_length,
+                options.hough_max_line_gap,
                 file_name,
                 "../../dataset/result/edges/",
-            )
-            .unwrap();
+            ) {
+                Ok(angle) => angle,
+                // 未检测到直线时不旋转
+                Err(oics::Error::NoFeatures(_)) => 0.0,
+                Err(error) => panic!("{}", error),
+            };
             transfer::rotate_mat(
                 &original_image,
                 hough_angle,
@@ -136,9 +173,9 @@ fn run_test(p: bool, h: bool, f: bool) {
         if f {
             let fft_start = instant.elapsed().as_millis();
 
-            let gray_image = transfer::transfer_rgb_image_to_gray_image(&original_image).unwrap();
+            let gray_image = transfer::transfer_rgb_image_to_gray_image(&detection_image).unwrap();
 
-            let fft_angle = oics::fft::get_angle_with_fft(
+            let fft_angle = match oics::fft::get_angle_with_fft(
                 &gray_image,
                 125.0,
                 150.0,
@@ -146,8 +183,12 @@ fn run_test(p: bool, h: bool, f: bool) {
                 75.0,
                 file_name,
                 "../../dataset/result/canny/",
-            )
-            .unwrap();
+            ) {
+                Ok(angle) => angle,
+                // 未检测到直线时不旋转
+                Err(oics::Error::NoFeatures(_)) => 0.0,
+                Err(error) => panic!("{}", error),
+            };
             transfer::rotate_mat(
                 &original_image,
                 fft_angle,
@@ -172,6 +213,44 @@ fn run_test(p: bool, h: bool, f: bool) {
             fft_run_time_array.push(fft_end - fft_start);
             fft_deviation_array.push((fft_angle - random_angle).abs());
         }
+
+        if r {
+            let radon_start = instant.elapsed().as_millis();
+            let radon_angle = oics::radon::get_result_with_radon(
+                &detection_image,
+                options.projection_max_angle,
+                options.projection_angle_step,
+                options.projection_max_width,
+                options.projection_max_height,
+                BinarizationStrategy::default(),
+                PeakInterpolation::NONE,
+            )
+            .unwrap()
+            .angle;
+            transfer::rotate_mat(
+                &original_image,
+                radon_angle,
+                1.0,
+                imgproc::INTER_LINEAR,
+                core::BORDER_CONSTANT,
+                Scalar::new(255.0, 255.0, 255.0, 0.0),
+                RotateClipStrategy::CONTAIN,
+            )
+            .unwrap()
+            .im_write(
+                Path::new("../../dataset/result/radon")
+                    .join(file_name)
+                    .to_str()
+                    .unwrap(),
+                ImageFormat::JPEG,
+                100,
+            )
+            .unwrap();
+
+            let radon_end = instant.elapsed().as_millis();
+            radon_run_time_array.push(radon_end - radon_start);
+            radon_deviation_array.push((radon_angle - random_angle).abs());
+        }
     }
 
     println!("基准测试");
@@ -193,13 +272,19 @@ fn run_test(p: bool, h: bool, f: bool) {
             fft_run_time_array.iter().sum::<u128>() / (len as u128)
         });
     }
+    if r {
+        println!("{:?}", {
+            let len = radon_run_time_array.len();
+            radon_run_time_array.iter().sum::<u128>() / (len as u128)
+        });
+    }
 
     println!("误差测试");
     if p {
         println!(
             "{}, {}, {}",
-            oics::calculate::get_arithmetic_mean(&projection_deviation_array),
-            oics::calculate::get_standard_deviation(&projection_deviation_array),
+            oics::calculate::get_arithmetic_mean(&projection_deviation_array).unwrap(),
+            oics::calculate::get_standard_deviation(&projection_deviation_array).unwrap(),
             {
                 let mut largest = projection_deviation_array[0];
                 for val in projection_deviation_array {
@@ -215,8 +300,8 @@ fn run_test(p: bool, h: bool, f: bool) {
     if h {
         println!(
             "{}, {}, {}",
-            oics::calculate::get_arithmetic_mean(&hough_deviation_array),
-            oics::calculate::get_standard_deviation(&hough_deviation_array),
+            oics::calculate::get_arithmetic_mean(&hough_deviation_array).unwrap(),
+            oics::calculate::get_standard_deviation(&hough_deviation_array).unwrap(),
             {
                 let mut largest = hough_deviation_array[0];
                 for val in hough_deviation_array {
@@ -232,8 +317,8 @@ fn run_test(p: bool, h: bool, f: bool) {
     if f {
         println!(
             "{}, {}, {}",
-            oics::calculate::get_arithmetic_mean(&fft_deviation_array),
-            oics::calculate::get_standard_deviation(&fft_deviation_array),
+            oics::calculate::get_arithmetic_mean(&fft_deviation_array).unwrap(),
+            oics::calculate::get_standard_deviation(&fft_deviation_array).unwrap(),
             {
                 let mut largest = fft_deviation_array[0];
                 for val in fft_deviation_array {
@@ -246,8 +331,76 @@ fn run_test(p: bool, h: bool, f: bool) {
             }
         );
     }
+    if r {
+        println!(
+            "{}, {}, {}",
+            oics::calculate::get_arithmetic_mean(&radon_deviation_array).unwrap(),
+            oics::calculate::get_standard_deviation(&radon_deviation_array).unwrap(),
+            {
+                let mut largest = radon_deviation_array[0];
+                for val in radon_deviation_array {
+                    if val > largest {
+                        largest = val;
+                    }
+                }
+
+                largest
+            }
+        );
+    }
+}
+
+const USAGE: &str =
+    "usage: omr-img-corrector [--options <options.json>] [--projection-resize-scale <scale>]";
+
+/// 打印错误信息后退出
+fn exit_with_error(message: impl std::fmt::Display) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
+
+/// ### 解析命令行参数
+///
+/// - `--options`: `CorrectionOptions` 的 JSON 文件，未指定的字段取默认值
+/// - `--projection-resize-scale`: 投影标准差方案基准测试中检测图像的缩放比例
+///
+/// 返回纠偏参数与检测图像的缩放比例
+fn parse_args() -> (CorrectionOptions, f64) {
+    let mut options = CorrectionOptions::default().hough(125.0, 15.0);
+    let mut projection_resize_scale = 0.2;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .unwrap_or_else(|| exit_with_error(format!("missing value for {}\n{}", arg, USAGE)));
+        match arg.as_str() {
+            "--options" => {
+                options = CorrectionOptions::load(&value).unwrap_or_else(|error| {
+                    exit_with_error(format!("failed to load {}: {}", value, error))
+                });
+            }
+            "--projection-resize-scale" => {
+                projection_resize_scale = match value.parse::<f64>() {
+                    Ok(scale) if scale > 0.0 => scale,
+                    _ => exit_with_error(format!(
+                        "projection resize scale must be positive, got {}",
+                        value
+                    )),
+                };
+            }
+            _ => exit_with_error(format!("unknown argument {}\n{}", arg, USAGE)),
+        }
+    }
+
+    (options, projection_resize_scale)
 }
 
 fn main() {
-    run_test(true, true, false);
+    let (options, projection_resize_scale) = parse_args();
+    if let Err(error) = options.validate() {
+        exit_with_error(error);
+    }
+
+    run_test(&options, projection_resize_scale, true, true, false, true);
 }
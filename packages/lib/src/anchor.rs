@@ -0,0 +1,262 @@
+use opencv::{
+    core::{Mat, Point2f, Scalar, Size2i, DECOMP_LU},
+    imgproc,
+    prelude::MatTraitConst,
+    types::{VectorOfPoint, VectorOfPoint2f, VectorOfVectorOfPoint},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::Result,
+    transfer::{self, TransformableMatrix},
+};
+
+/// ### 定位块检测器
+///
+/// 在二值图中查找答题卡四角印刷的实心黑色方块
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct AnchorDetector {
+    /// 定位块面积占图像面积的最小比例
+    pub min_area_ratio: f64,
+    /// 定位块面积占图像面积的最大比例
+    pub max_area_ratio: f64,
+    /// 定位块轮廓面积与其四边形面积之比的最小值，用于排除空心方框
+    pub min_fill_ratio: f64,
+    /// 定位块最长边与最短边之比的最大值
+    pub max_side_ratio: f64,
+    /// 输出图像中定位块中心到图像边缘的距离
+    pub output_margin: i32,
+    /// 定位块中心所围成矩形的输出宽度，不大于 0 时根据定位块间距自动计算
+    pub output_width: i32,
+    /// 定位块中心所围成矩形的输出高度，不大于 0 时根据定位块间距自动计算
+    pub output_height: i32,
+}
+impl Default for AnchorDetector {
+    fn default() -> Self {
+        Self {
+            min_area_ratio: 0.0001,
+            max_area_ratio: 0.01,
+            min_fill_ratio: 0.85,
+            max_side_ratio: 1.5,
+            output_margin: 20,
+            output_width: 0,
+            output_height: 0,
+        }
+    }
+}
+
+/// 答题卡四角定位块的中心点
+#[derive(Clone, Debug)]
+pub struct Anchors {
+    pub top_left: Point2f,
+    pub top_right: Point2f,
+    pub bottom_right: Point2f,
+    pub bottom_left: Point2f,
+}
+
+fn get_distance(a: &Point2f, b: &Point2f) -> f32 {
+    ((a.x - b.x).powf(2.0) + (a.y - b.y).powf(2.0)).sqrt()
+}
+
+/// 判断轮廓是否为实心方块，是则返回其中心点
+fn get_anchor_center(
+    contour: &VectorOfPoint,
+    detector: &AnchorDetector,
+    image_area: f64,
+) -> Result<Option<Point2f>> {
+    let area = imgproc::contour_area(contour, false)?;
+    if area < image_area * detector.min_area_ratio || area > image_area * detector.max_area_ratio {
+        return Ok(None);
+    }
+
+    // 将轮廓近似为多边形，定位块应为凸四边形
+    let mut polygon = VectorOfPoint::new();
+    imgproc::approx_poly_dp(
+        contour,
+        &mut polygon,
+        0.05 * imgproc::arc_length(contour, true)?,
+        true,
+    )?;
+    if polygon.len() != 4 || !imgproc::is_contour_convex(&polygon)? {
+        return Ok(None);
+    }
+
+    // 四条边长度相近
+    let corners: Vec<Point2f> = polygon
+        .iter()
+        .map(|point| Point2f::new(point.x as f32, point.y as f32))
+        .collect();
+    let sides: Vec<f32> = (0..4)
+        .map(|index| get_distance(&corners[index], &corners[(index + 1) % 4]))
+        .collect();
+    let longest_side = sides.iter().cloned().fold(0.0f32, f32::max);
+    let shortest_side = sides.iter().cloned().fold(f32::MAX, f32::min);
+    if shortest_side <= 0.0 || (longest_side / shortest_side) as f64 > detector.max_side_ratio {
+        return Ok(None);
+    }
+
+    // 轮廓填满四边形，排除空心方框
+    let polygon_area = imgproc::contour_area(&polygon, false)?;
+    if polygon_area <= 0.0 || area / polygon_area < detector.min_fill_ratio {
+        return Ok(None);
+    }
+
+    let moments = imgproc::moments(contour, false)?;
+    if moments.m00 == 0.0 {
+        return Ok(None);
+    }
+
+    Ok(Some(Point2f::new(
+        (moments.m10 / moments.m00) as f32,
+        (moments.m01 / moments.m00) as f32,
+    )))
+}
+
+/// ### 查找答题卡四角的定位块
+///
+/// **参数列表：**
+/// - `src`: 三通道图像
+/// - `detector`: 定位块检测器参数
+///
+/// 在图像的四个象限中分别选取距离对应角点最近的定位块，任一象限缺失定位块时返回 `None`
+pub fn find_anchors(
+    src: &TransformableMatrix,
+    detector: &AnchorDetector,
+) -> Result<Option<Anchors>> {
+    // 反相二值化，使黑色定位块成为前景
+    let thresh_mat = {
+        let gray_image = transfer::transfer_rgb_image_to_gray_image(src)?;
+        let mut dst = Mat::default();
+        imgproc::threshold(
+            gray_image.get_mat(),
+            &mut dst,
+            127.0,
+            255.0,
+            imgproc::THRESH_BINARY_INV,
+        )?;
+        dst
+    };
+
+    let mut contours = VectorOfVectorOfPoint::new();
+    imgproc::find_contours(
+        &thresh_mat,
+        &mut contours,
+        imgproc::RETR_EXTERNAL,
+        imgproc::CHAIN_APPROX_SIMPLE,
+        opencv::core::Point::new(0, 0),
+    )?;
+
+    let size = thresh_mat.size()?;
+    let (width, height) = (size.width as f32, size.height as f32);
+    let image_area = size.width as f64 * size.height as f64;
+
+    let mut centers = vec![];
+    for contour in contours.iter() {
+        if let Some(center) = get_anchor_center(&contour, detector, image_area)? {
+            centers.push(center);
+        }
+    }
+
+    // 在指定象限内查找距离角点最近的定位块
+    let find_nearest = |corner: Point2f| {
+        centers
+            .iter()
+            .filter(|center| {
+                ((center.x < width / 2.0) == (corner.x < width / 2.0))
+                    && ((center.y < height / 2.0) == (corner.y < height / 2.0))
+            })
+            .min_by(|a, b| get_distance(a, &corner).total_cmp(&get_distance(b, &corner)))
+            .copied()
+    };
+
+    match (
+        find_nearest(Point2f::new(0.0, 0.0)),
+        find_nearest(Point2f::new(width, 0.0)),
+        find_nearest(Point2f::new(width, height)),
+        find_nearest(Point2f::new(0.0, height)),
+    ) {
+        (Some(top_left), Some(top_right), Some(bottom_right), Some(bottom_left)) => {
+            Ok(Some(Anchors {
+                top_left,
+                top_right,
+                bottom_right,
+                bottom_left,
+            }))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// ### 根据定位块对图像进行透视变换
+///
+/// 计算定位块中心到标准矩形四角的单应性矩阵，并将图像变换至标准矩形
+///
+/// **参数列表：**
+/// - `src`: 待变换的图像
+/// - `anchors`: 定位块中心点
+/// - `detector`: 定位块检测器参数，提供输出尺寸与边距
+///
+pub fn warp_to_anchors(
+    src: &TransformableMatrix,
+    anchors: &Anchors,
+    detector: &AnchorDetector,
+) -> Result<TransformableMatrix> {
+    let width = if detector.output_width > 0 {
+        detector.output_width as f32
+    } else {
+        get_distance(&anchors.top_left, &anchors.top_right)
+            .max(get_distance(&anchors.bottom_left, &anchors.bottom_right))
+    };
+    let height = if detector.output_height > 0 {
+        detector.output_height as f32
+    } else {
+        get_distance(&anchors.top_left, &anchors.bottom_left)
+            .max(get_distance(&anchors.top_right, &anchors.bottom_right))
+    };
+    let margin = detector.output_margin as f32;
+
+    let src_points = VectorOfPoint2f::from(vec![
+        anchors.top_left,
+        anchors.top_right,
+        anchors.bottom_right,
+        anchors.bottom_left,
+    ]);
+    let dst_points = VectorOfPoint2f::from(vec![
+        Point2f::new(margin, margin),
+        Point2f::new(margin + width, margin),
+        Point2f::new(margin + width, margin + height),
+        Point2f::new(margin, margin + height),
+    ]);
+    let homography = imgproc::get_perspective_transform(&src_points, &dst_points, DECOMP_LU)?;
+
+    let mut dst = Mat::default();
+    imgproc::warp_perspective(
+        src.get_mat(),
+        &mut dst,
+        &homography,
+        Size2i::new(
+            (width + margin * 2.0).ceil() as i32,
+            (height + margin * 2.0).ceil() as i32,
+        ),
+        imgproc::INTER_LINEAR,
+        opencv::core::BORDER_CONSTANT,
+        Scalar::new(255.0, 255.0, 255.0, 0.0), // b g r
+    )?;
+
+    Ok(TransformableMatrix::from_matrix(&dst))
+}
+
+/// ### 利用四角定位块对图像进行透视纠正
+///
+/// 未找到完整的四个定位块时返回 `None`
+pub fn correct_with_anchors(
+    src: &TransformableMatrix,
+    detector: &AnchorDetector,
+) -> Result<Option<TransformableMatrix>> {
+    match find_anchors(src, detector)? {
+        Some(anchors) => Ok(Some(warp_to_anchors(src, &anchors, detector)?)),
+        None => Ok(None),
+    }
+}
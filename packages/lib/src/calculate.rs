@@ -1,23 +1,357 @@
-/// 计算泛型数组的平均数
-pub fn get_arithmetic_mean(vec: &Vec<f64>) -> f64 {
-    let length = vec.len();
-    let mut sum = vec[0];
-    for i in 1..length {
-        sum = sum + vec[i];
+use crate::{
+    error::{Error, Result},
+    types::PeakInterpolation,
+};
+
+/// 计算平均数，调用方需保证数组非空
+fn get_mean(vec: &[f64]) -> f64 {
+    vec.iter().sum::<f64>() / vec.len() as f64
+}
+
+/// 计算标准差，调用方需保证数组非空
+fn get_deviation(vec: &[f64]) -> f64 {
+    let arithmetic_mean = get_mean(vec);
+    let sum = vec
+        .iter()
+        .map(|item| (item - arithmetic_mean).powf(2.0))
+        .sum::<f64>();
+
+    (sum / vec.len() as f64).powf(0.5)
+}
+
+/// 计算泛型数组的平均数，数组为空时返回 `Error::InvalidParameter`
+pub fn get_arithmetic_mean(vec: &[f64]) -> Result<f64> {
+    if vec.is_empty() {
+        return Err(Error::InvalidParameter(String::from(
+            "cannot calculate the mean of an empty array",
+        )));
     }
 
-    return sum / length as f64;
+    Ok(get_mean(vec))
 }
 
-/// 计算泛型数组的标准差
-pub fn get_standard_deviation(vec: &Vec<f64>) -> f64 {
-    let arithmetic_mean = get_arithmetic_mean(vec);
-    let length = vec.len();
-    let mut sum = (vec[0] - arithmetic_mean).powf(2.0);
+/// 计算泛型数组的标准差，数组为空时返回 `Error::InvalidParameter`
+pub fn get_standard_deviation(vec: &[f64]) -> Result<f64> {
+    if vec.is_empty() {
+        return Err(Error::InvalidParameter(String::from(
+            "cannot calculate the standard deviation of an empty array",
+        )));
+    }
+
+    Ok(get_deviation(vec))
+}
+
+/// 获取曲线峰值所在的主峰范围
+///
+/// 从峰值出发向两侧遍历，直至曲线不再单调下降，返回主峰的左右边界下标
+fn get_peak_lobe(values: &[f64], peak_index: usize) -> (usize, usize) {
+    let mut left = peak_index;
+    while left > 0 && values[left - 1] <= values[left] {
+        left -= 1;
+    }
+    let mut right = peak_index;
+    while right + 1 < values.len() && values[right + 1] <= values[right] {
+        right += 1;
+    }
+
+    (left, right)
+}
+
+/// 根据曲线峰值的尖锐程度计算置信度，取值范围为 [0, 1]
+///
+/// 以峰值与曲线均值之差作为峰高，主峰之外的次高峰越接近峰值，置信度越低
+///
+/// `peak_index` 超出曲线范围时返回 0
+pub fn get_peak_confidence(values: &[f64], peak_index: usize) -> f64 {
+    if peak_index >= values.len() {
+        return 0.0;
+    }
+
+    let arithmetic_mean = get_mean(values);
+    let peak = values[peak_index];
+    let height = peak - arithmetic_mean;
+    if height <= 0.0 {
+        // 曲线平坦，无法判断峰值
+        return 0.0;
+    }
+
+    let (left, right) = get_peak_lobe(values, peak_index);
+    // 主峰之外的次高峰
+    let runner_up = values
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| *index < left || *index > right)
+        .map(|(_, value)| *value)
+        .fold(None, |max: Option<f64>, value| match max {
+            Some(max) if max >= value => Some(max),
+            _ => Some(value),
+        });
+
+    match runner_up {
+        Some(runner_up) => ((peak - runner_up) / height).max(0.0).min(1.0),
+        None => 1.0,
+    }
+}
+
+/// 计算曲线峰值的半高半宽，单位为采样间隔
+///
+/// 半高取峰值与曲线均值的中点，用于估计峰值位置的误差范围。
+/// `peak_index` 超出曲线范围时返回整条曲线的半宽
+pub fn get_peak_half_width(values: &[f64], peak_index: usize) -> f64 {
+    if peak_index >= values.len() {
+        return (values.len() as f64 / 2.0).max(0.5);
+    }
+
+    let arithmetic_mean = get_mean(values);
+    let half_height = (values[peak_index] + arithmetic_mean) / 2.0;
+
+    let mut left = peak_index;
+    while left > 0 && values[left - 1] >= half_height {
+        left -= 1;
+    }
+    let mut right = peak_index;
+    while right + 1 < values.len() && values[right + 1] >= half_height {
+        right += 1;
+    }
+
+    // 至少为半个采样间隔
+    ((right - left) as f64 / 2.0).max(0.5)
+}
+
+/// 根据直线角度的一致程度计算置信度与估计误差
+///
+/// 置信度为与目标角度相差不超过 `range` 的直线所占比例，
+/// 估计误差为这些直线角度的标准差，且至少为 `range` 的一半。
+/// 夹角按 90° 周期计算，因此 -44.95° 与 44.95° 被视为相近的角度
+pub fn get_angles_agreement(angles: &[f64], target_angle: f64, range: f64) -> (f64, f64) {
+    // 以与目标角度的有向夹角代替角度本身，避免跨越 ±45° 时标准差失真
+    let agreed_offsets: Vec<f64> = angles
+        .iter()
+        .map(|angle| fold_line_angle(angle - target_angle, DEFAULT_MIN_LINE_ANGLE))
+        .filter(|offset| offset.abs() < range)
+        .collect();
+    if agreed_offsets.is_empty() {
+        return (0.0, 45.0);
+    }
+
+    let confidence = agreed_offsets.len() as f64 / angles.len() as f64;
+    let uncertainty = get_deviation(&agreed_offsets).max(range / 2.0);
+
+    (confidence, uncertainty)
+}
+
+/// 文档直线方向角的周期：水平线与垂直线对应同一偏转角
+pub const LINE_ANGLE_PERIOD: f64 = 90.0;
+
+/// 直线方向角的默认折叠区间下限，折叠后的取值范围为 [-45, 45)
+pub const DEFAULT_MIN_LINE_ANGLE: f64 = -LINE_ANGLE_PERIOD / 2.0;
+
+/// ### 折叠直线方向角
+///
+/// 利用文档直线 90° 的周期性，将任意方向角折叠至 `[min_angle, min_angle + 90)` 区间。
+///
+/// 如 `min_angle` 为 -45 时，50° 折叠为 -40°，-50° 折叠为 40°，135° 折叠为 -45°
+pub fn fold_line_angle(angle: f64, min_angle: f64) -> f64 {
+    (angle - min_angle).rem_euclid(LINE_ANGLE_PERIOD) + min_angle
+}
+
+/// 两个直线方向角之间的最小夹角，考虑 90° 的周期，取值范围为 [0, 45]
+pub fn get_line_angle_distance(angle_a: f64, angle_b: f64) -> f64 {
+    fold_line_angle(angle_a - angle_b, DEFAULT_MIN_LINE_ANGLE).abs()
+}
+
+/// ### 计算直线方向角的加权圆周中位数
+///
+/// **参数列表：**
+/// - `angles`: 角度数组
+/// - `weights`: 与角度一一对应的非负权重
+///
+/// 在输入角度中选取与其余角度加权夹角之和最小者，夹角按 90° 周期计算，
+/// 因此 -44.9° 与 44.9° 被视为相近的角度。输入为空时返回 `None`
+pub fn get_circular_weighted_median(angles: &[f64], weights: &[f64]) -> Option<f64> {
+    let get_cost = |target_angle: f64| -> f64 {
+        angles
+            .iter()
+            .zip(weights.iter())
+            .map(|(angle, weight)| weight * get_line_angle_distance(*angle, target_angle))
+            .sum()
+    };
+
+    let mut median: Option<(f64, f64)> = None;
+    for angle in angles.iter() {
+        let cost = get_cost(*angle);
+        match median {
+            Some((_, min_cost)) if min_cost <= cost => {}
+            _ => median = Some((*angle, cost)),
+        }
+    }
+
+    median.map(|(angle, _)| angle)
+}
+
+/// 角度直方图中的峰值
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AngleHistogramPeak {
+    /// 峰值角度，为峰值附近各角度的加权平均
+    pub angle: f64,
+    /// 平滑后的峰值权重
+    pub weight: f64,
+}
+
+/// 角度直方图的统计结果
+#[derive(Clone, Debug)]
+pub struct AngleHistogram {
+    /// 按权重从高到低排列的峰值
+    pub peaks: Vec<AngleHistogramPeak>,
+    /// 最高峰的置信度，取值范围为 [0, 1]
+    pub confidence: f64,
+    /// 最高峰角度的估计误差（±deg）
+    pub uncertainty: f64,
+}
+
+/// ### 统计加权角度直方图并查找峰值
+///
+/// **参数列表：**
+/// - `angles`: 角度数组
+/// - `weights`: 与角度一一对应的权重，如线段长度
+/// - `bin_width`: 直方图的组距
+/// - `smoothing_radius`: 三角核平滑的半径，单位为组
+/// - `max_peaks`: 返回的最大峰值数目
+///
+/// 角度按 90° 周期统计，首尾两组相邻，返回的峰值角度位于 [-45, 45) 区间。
+/// 时间复杂度与角度数目及直方图组数成线性关系
+pub fn get_angle_histogram(
+    angles: &[f64],
+    weights: &[f64],
+    bin_width: f64,
+    smoothing_radius: usize,
+    max_peaks: usize,
+) -> AngleHistogram {
+    if angles.is_empty() {
+        return AngleHistogram {
+            peaks: vec![],
+            confidence: 0.0,
+            uncertainty: 45.0,
+        };
+    }
+
+    // 直方图覆盖一个完整的直线方向角周期，首尾相接，使 -45° 与 45° 附近的角度落入相邻的组
+    let bins_count = ((LINE_ANGLE_PERIOD / bin_width).ceil() as usize).max(1);
+    let get_index = |angle: f64| {
+        let offset = fold_line_angle(angle, DEFAULT_MIN_LINE_ANGLE) - DEFAULT_MIN_LINE_ANGLE;
+        (offset / bin_width).floor() as usize % bins_count
+    };
+    let wrap = |index: i64| index.rem_euclid(bins_count as i64) as usize;
+
+    let mut histogram = vec![0.0; bins_count];
+    for (angle, weight) in angles.iter().zip(weights.iter()) {
+        histogram[get_index(*angle)] += weight;
+    }
+
+    // 三角核平滑
+    let radius = smoothing_radius as i64;
+    let kernel_sum = ((radius + 1) * (radius + 1)) as f64;
+    let smoothed: Vec<f64> = (0..bins_count as i64)
+        .map(|index| {
+            (-radius..=radius)
+                .map(|offset| histogram[wrap(index + offset)] * (radius + 1 - offset.abs()) as f64)
+                .sum::<f64>()
+                / kernel_sum
+        })
+        .collect();
+
+    // 查找局部峰值，平台只取最左侧一点
+    let mut peak_indexes: Vec<usize> = (0..bins_count)
+        .filter(|index| {
+            let value = smoothed[*index];
+            value > 0.0
+                && (bins_count == 1
+                    || (smoothed[wrap(*index as i64 - 1)] < value
+                        && smoothed[wrap(*index as i64 + 1)] <= value))
+        })
+        .collect();
+    peak_indexes.sort_by(|a, b| smoothed[*b].total_cmp(&smoothed[*a]));
+    peak_indexes.truncate(max_peaks.max(1));
+
+    // 以峰值附近各角度的加权平均作为峰值角度，获得小于组距的精度
+    let peaks: Vec<AngleHistogramPeak> = peak_indexes
+        .iter()
+        .map(|peak_index| {
+            let center = DEFAULT_MIN_LINE_ANGLE + (*peak_index as f64 + 0.5) * bin_width;
+            let (mut offset_sum, mut weight_sum) = (0.0, 0.0);
+            for (angle, weight) in angles.iter().zip(weights.iter()) {
+                let distance = wrap(get_index(*angle) as i64 - *peak_index as i64);
+                if distance.min(bins_count - distance) as i64 <= radius {
+                    offset_sum += fold_line_angle(angle - center, DEFAULT_MIN_LINE_ANGLE) * weight;
+                    weight_sum += weight;
+                }
+            }
+            // 平滑后权重为正的峰值附近必然存在权重为正的角度
+            AngleHistogramPeak {
+                angle: fold_line_angle(center + offset_sum / weight_sum, DEFAULT_MIN_LINE_ANGLE),
+                weight: smoothed[*peak_index],
+            }
+        })
+        .collect();
+
+    let (confidence, uncertainty) = match peak_indexes.first() {
+        Some(peak_index) => {
+            // 将曲线循环平移至峰值位于中央，以便按普通曲线计算置信度与半高宽
+            let middle = bins_count / 2;
+            let centered: Vec<f64> = (0..bins_count as i64)
+                .map(|index| smoothed[wrap(index - middle as i64 + *peak_index as i64)])
+                .collect();
+            (
+                get_peak_confidence(&centered, middle),
+                get_peak_half_width(&centered, middle) * bin_width,
+            )
+        }
+        None => (0.0, 45.0),
+    };
+
+    AngleHistogram {
+        peaks,
+        confidence,
+        uncertainty,
+    }
+}
+
+/// 通过插值估计曲线峰值的亚采样偏移量，单位为采样间隔，取值范围为 [-0.5, 0.5]
+///
+/// 峰值位于曲线两端或相邻两点无法构成峰时返回 0
+pub fn get_peak_offset(values: &[f64], peak_index: usize, interpolation: PeakInterpolation) -> f64 {
+    if peak_index == 0 || peak_index + 1 >= values.len() {
+        return 0.0;
+    }
+
+    let (left, center, right) = match interpolation {
+        PeakInterpolation::NONE => return 0.0,
+        PeakInterpolation::PARABOLIC => (
+            values[peak_index - 1],
+            values[peak_index],
+            values[peak_index + 1],
+        ),
+        PeakInterpolation::GAUSSIAN => {
+            if values[peak_index - 1] <= 0.0
+                || values[peak_index] <= 0.0
+                || values[peak_index + 1] <= 0.0
+            {
+                return 0.0;
+            }
+            // 对数域下的高斯曲线即为抛物线
+            (
+                values[peak_index - 1].ln(),
+                values[peak_index].ln(),
+                values[peak_index + 1].ln(),
+            )
+        }
+    };
 
-    for i in 1..length {
-        sum = sum + (vec[i] - arithmetic_mean).powf(2.0);
+    let denominator = left - 2.0 * center + right;
+    if denominator >= 0.0 {
+        // 三点不构成峰
+        return 0.0;
     }
 
-    return (sum / length as f64).powf(0.5);
+    (0.5 * (left - right) / denominator).max(-0.5).min(0.5)
 }
@@ -0,0 +1,395 @@
+use crate::{
+    error::Result,
+    fft, hough,
+    omr::{self, OmrResult, ProjectionSearchMode},
+    pipeline::PreprocessPipeline,
+    projection, radon,
+    transfer::{self, TransformableMatrix},
+    types::{BinarizationStrategy, IlluminationStrategy, PeakInterpolation},
+};
+
+/// ### 偏转角检测器
+///
+/// 统一各类纠偏算法的调用方式：输入图像，输出 `OmrResult`。
+///
+/// 输入图像应为通过 `imgcodecs::IMREAD_COLOR` 读取的三通道图像，
+/// 灰度化、二值化等预处理由各检测器自行完成。
+///
+/// 用例
+/// ```rust,no_run
+/// # use oics::detector::{HoughDetector, ProjectionDetector, SkewDetector};
+/// # use oics::transfer::TransformableMatrix;
+/// # use opencv::imgcodecs;
+///
+/// let src = TransformableMatrix::new("01234.jpg", imgcodecs::IMREAD_COLOR).unwrap();
+/// let detectors: Vec<Box<dyn SkewDetector>> = vec![
+///     Box::new(ProjectionDetector::default()),
+///     Box::new(HoughDetector::default()),
+/// ];
+/// for detector in detectors.iter() {
+///     let result = detector.detect(&src).unwrap();
+///     println!("{}: {}deg", detector.name(), result.angle);
+/// }
+/// ```
+pub trait SkewDetector: Send + Sync {
+    /// 检测器名称
+    fn name(&self) -> &'static str;
+
+    /// 检测图像的偏转角
+    fn detect(&self, src: &TransformableMatrix) -> Result<OmrResult>;
+}
+
+/// ### 投影标准差检测器
+///
+/// 对应 `omr::get_result_from_projection`
+#[derive(Clone, Debug)]
+pub struct ProjectionDetector {
+    /// 投影的最大偏转角度
+    pub max_angle: u16,
+    /// 投影的角度步长
+    pub angle_step: f64,
+    /// 投影图像的最大宽度，不大于 0 时不限制宽度
+    pub max_width: i32,
+    /// 投影图像的最大高度，不大于 0 时不限制高度
+    pub max_height: i32,
+    /// 角度搜索方式
+    pub search_mode: ProjectionSearchMode,
+    /// 峰值插值方式
+    pub peak_interpolation: PeakInterpolation,
+    /// 二值化方式
+    pub binarization: BinarizationStrategy,
+    /// 预处理流水线，不为 `None` 时替代内置的灰度化、腐蚀、缩放与二值化步骤，
+    /// 此时 `max_width`、`max_height` 与 `binarization` 不再生效
+    pub pipeline: Option<PreprocessPipeline>,
+    /// 计算投影标准差的并行度，不大于 1 时在当前线程中计算
+    pub threads: usize,
+}
+impl Default for ProjectionDetector {
+    fn default() -> Self {
+        Self {
+            max_angle: 45,
+            angle_step: 0.2,
+            max_width: 248,
+            max_height: 230,
+            search_mode: ProjectionSearchMode::Exhaustive,
+            peak_interpolation: PeakInterpolation::NONE,
+            binarization: BinarizationStrategy::default(),
+            pipeline: None,
+            threads: 1,
+        }
+    }
+}
+impl SkewDetector for ProjectionDetector {
+    fn name(&self) -> &'static str {
+        "projection"
+    }
+
+    fn detect(&self, src: &TransformableMatrix) -> Result<OmrResult> {
+        if let Some(pipeline) = &self.pipeline {
+            return omr::get_result_from_projection_with_pipeline(src, pipeline, self);
+        }
+
+        omr::get_result_from_projection(src.get_mat(), self)
+    }
+}
+
+/// ### 投影标准差扫描检测器
+///
+/// 对应 `projection::get_result_with_projections`
+#[derive(Clone, Debug)]
+pub struct ProjectionSweepDetector {
+    /// 投影的最大偏转角度
+    pub max_angle: u16,
+    /// 投影的角度步长
+    pub step: f64,
+    /// 投影前图像的缩放比例
+    pub resize_scale: f64,
+    /// 扫描所用的线程数
+    pub threads: usize,
+    /// 峰值插值方式
+    pub peak_interpolation: PeakInterpolation,
+    /// 二值化方式
+    pub binarization: BinarizationStrategy,
+    /// 光照归一化方式
+    pub illumination: IlluminationStrategy,
+}
+impl Default for ProjectionSweepDetector {
+    fn default() -> Self {
+        Self {
+            max_angle: 45,
+            step: 0.2,
+            resize_scale: 0.2,
+            threads: 1,
+            peak_interpolation: PeakInterpolation::NONE,
+            binarization: BinarizationStrategy::default(),
+            illumination: IlluminationStrategy::NONE,
+        }
+    }
+}
+impl SkewDetector for ProjectionSweepDetector {
+    fn name(&self) -> &'static str {
+        "projection_sweep"
+    }
+
+    fn detect(&self, src: &TransformableMatrix) -> Result<OmrResult> {
+        projection::get_result_with_projections(src, self)
+    }
+}
+
+/// ### 边缘检测检测器
+///
+/// 对应 `omr::get_result_from_edges_detection`
+#[derive(Clone, Debug)]
+pub struct EdgesDetector {
+    /// 感知的最小线段长度
+    pub min_line_length: f64,
+    /// 感知的线段最大中断长度
+    pub max_line_gap: f64,
+}
+impl Default for EdgesDetector {
+    fn default() -> Self {
+        Self {
+            min_line_length: 125.0,
+            max_line_gap: 15.0,
+        }
+    }
+}
+impl SkewDetector for EdgesDetector {
+    fn name(&self) -> &'static str {
+        "edges"
+    }
+
+    fn detect(&self, src: &TransformableMatrix) -> Result<OmrResult> {
+        omr::get_result_from_edges_detection(src.get_mat(), self.min_line_length, self.max_line_gap)
+    }
+}
+
+/// ### 霍夫变换检测器
+///
+/// 对应 `hough::get_angle_with_hough`
+#[derive(Clone, Debug)]
+pub struct HoughDetector {
+    /// 感知的最小线段长度
+    pub min_line_length: f64,
+    /// 感知的线段最大中断长度
+    pub max_line_gap: f64,
+    /// 直线图的输出路径，为 `None` 时不输出
+    pub lined_image_output_path: Option<String>,
+}
+impl Default for HoughDetector {
+    fn default() -> Self {
+        Self {
+            min_line_length: 125.0,
+            max_line_gap: 15.0,
+            lined_image_output_path: None,
+        }
+    }
+}
+impl SkewDetector for HoughDetector {
+    fn name(&self) -> &'static str {
+        "hough"
+    }
+
+    fn detect(&self, src: &TransformableMatrix) -> Result<OmrResult> {
+        hough::get_result_with_hough(
+            &transfer::transfer_rgb_image_to_gray_image(src)?,
+            self.min_line_length,
+            self.max_line_gap,
+            self.lined_image_output_path.as_deref(),
+        )
+    }
+}
+
+/// ### 傅里叶变换检测器
+///
+/// 对应 `omr::get_result_from_fourier_transform`
+#[derive(Clone, Debug)]
+pub struct FourierTransformDetector {
+    /// canny 边缘检测的弱阈值
+    pub canny_threshold_weak: f64,
+    /// canny 边缘检测的强阈值
+    pub canny_threshold_strong: f64,
+    /// 感知的最小线段长度
+    pub min_line_length: f64,
+    /// 感知的线段最大中断长度
+    pub max_line_gap: f64,
+}
+impl Default for FourierTransformDetector {
+    fn default() -> Self {
+        Self {
+            canny_threshold_weak: 125.0,
+            canny_threshold_strong: 150.0,
+            min_line_length: 125.0,
+            max_line_gap: 15.0,
+        }
+    }
+}
+impl SkewDetector for FourierTransformDetector {
+    fn name(&self) -> &'static str {
+        "fourier_transform"
+    }
+
+    fn detect(&self, src: &TransformableMatrix) -> Result<OmrResult> {
+        omr::get_result_from_fourier_transform(
+            src.get_mat(),
+            self.canny_threshold_weak,
+            self.canny_threshold_strong,
+            self.min_line_length,
+            self.max_line_gap,
+        )
+    }
+}
+
+/// ### 快速傅里叶变换检测器
+///
+/// 对应 `fft::get_angle_with_fft`
+#[derive(Clone, Debug)]
+pub struct FftDetector {
+    /// canny 边缘检测的弱阈值
+    pub canny_threshold_1: f64,
+    /// canny 边缘检测的强阈值
+    pub canny_threshold_2: f64,
+    /// 感知的最小线段长度
+    pub min_line_length: f64,
+    /// 感知的线段最大中断长度
+    pub max_line_gap: f64,
+    /// 直线图的输出路径，为 `None` 时不输出
+    pub lined_image_output_path: Option<String>,
+}
+impl Default for FftDetector {
+    fn default() -> Self {
+        Self {
+            canny_threshold_1: 125.0,
+            canny_threshold_2: 150.0,
+            min_line_length: 125.0,
+            max_line_gap: 15.0,
+            lined_image_output_path: None,
+        }
+    }
+}
+impl SkewDetector for FftDetector {
+    fn name(&self) -> &'static str {
+        "fft"
+    }
+
+    fn detect(&self, src: &TransformableMatrix) -> Result<OmrResult> {
+        fft::get_result_with_fft(
+            &transfer::transfer_rgb_image_to_gray_image(src)?,
+            self.canny_threshold_1,
+            self.canny_threshold_2,
+            self.min_line_length,
+            self.max_line_gap,
+            self.lined_image_output_path.as_deref(),
+        )
+    }
+}
+
+/// ### 频谱极坐标投影检测器
+///
+/// 对应 `fft::get_result_with_fft_spectrum`
+#[derive(Clone, Debug)]
+pub struct FftSpectrumDetector {
+    /// 最大偏转角度
+    pub max_angle: u16,
+    /// 角度步长
+    pub angle_step: f64,
+    /// 峰值插值方式
+    pub peak_interpolation: PeakInterpolation,
+}
+impl Default for FftSpectrumDetector {
+    fn default() -> Self {
+        Self {
+            max_angle: 45,
+            angle_step: 0.2,
+            peak_interpolation: PeakInterpolation::NONE,
+        }
+    }
+}
+impl SkewDetector for FftSpectrumDetector {
+    fn name(&self) -> &'static str {
+        "fft_spectrum"
+    }
+
+    fn detect(&self, src: &TransformableMatrix) -> Result<OmrResult> {
+        fft::get_result_with_fft_spectrum(
+            &transfer::transfer_rgb_image_to_gray_image(src)?,
+            self.max_angle,
+            self.angle_step,
+            self.peak_interpolation,
+        )
+    }
+}
+
+/// ### Radon 变换检测器
+///
+/// 对应 `radon::get_result_with_radon`
+#[derive(Clone, Debug)]
+pub struct RadonDetector {
+    /// 最大偏转角度
+    pub max_angle: u16,
+    /// 角度步长
+    pub angle_step: f64,
+    /// 变换图像的最大宽度
+    pub max_width: i32,
+    /// 变换图像的最大高度
+    pub max_height: i32,
+    /// 二值化方式
+    pub binarization: BinarizationStrategy,
+    /// 峰值插值方式
+    pub peak_interpolation: PeakInterpolation,
+}
+impl Default for RadonDetector {
+    fn default() -> Self {
+        Self {
+            max_angle: 45,
+            angle_step: 0.2,
+            max_width: 248,
+            max_height: 230,
+            binarization: BinarizationStrategy::default(),
+            peak_interpolation: PeakInterpolation::NONE,
+        }
+    }
+}
+impl SkewDetector for RadonDetector {
+    fn name(&self) -> &'static str {
+        "radon"
+    }
+
+    fn detect(&self, src: &TransformableMatrix) -> Result<OmrResult> {
+        radon::get_result_with_radon(
+            src,
+            self.max_angle,
+            self.angle_step,
+            self.max_width,
+            self.max_height,
+            self.binarization,
+            self.peak_interpolation,
+        )
+    }
+}
+
+/// ### 带预处理流水线的检测器
+///
+/// 先对输入图像应用预处理流水线，再交由内部检测器处理。
+/// 流水线输出单通道图像时会转换回三通道图像，以符合检测器的输入要求
+pub struct PipelineDetector {
+    /// 预处理流水线
+    pub pipeline: PreprocessPipeline,
+    /// 内部检测器
+    pub detector: Box<dyn SkewDetector>,
+}
+impl PipelineDetector {
+    pub fn new(pipeline: PreprocessPipeline, detector: Box<dyn SkewDetector>) -> Self {
+        Self { pipeline, detector }
+    }
+}
+impl SkewDetector for PipelineDetector {
+    fn name(&self) -> &'static str {
+        self.detector.name()
+    }
+
+    fn detect(&self, src: &TransformableMatrix) -> Result<OmrResult> {
+        self.detector
+            .detect(&self.pipeline.apply_for_detector(src)?)
+    }
+}
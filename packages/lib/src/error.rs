@@ -0,0 +1,111 @@
+use std::fmt;
+
+use opencv::{core::Mat, prelude::MatTraitConst};
+
+/// ### oics 的错误类型
+///
+/// 公开的函数在输入不合法或处理失败时返回该错误，而不是 panic
+#[derive(Debug)]
+pub enum Error {
+    /// 输入文件不存在或无法解码为图像
+    UnreadableInput(String),
+    /// 图像为空，即宽或高为 0
+    EmptyImage,
+    /// 图像中未找到可用于计算的直线或特征
+    NoFeatures(String),
+    /// 参数不合法
+    InvalidParameter(String),
+    /// 答题卡模板、答案等配置内容不合法
+    InvalidConfig(String),
+    /// OpenCV 调用失败
+    OpenCV(opencv::Error),
+    /// 文件读写失败
+    Io(std::io::Error),
+    /// JSON 解析或序列化失败
+    Json(serde_json::Error),
+    /// 线程池已停止运行，或线程池中的任务发生 panic
+    WorkerPool(String),
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnreadableInput(path) => write!(f, "failed to read image: {}", path),
+            Self::EmptyImage => write!(f, "image is empty"),
+            Self::NoFeatures(message) => write!(f, "no features found: {}", message),
+            Self::InvalidParameter(message) => write!(f, "invalid parameter: {}", message),
+            Self::InvalidConfig(message) => write!(f, "invalid configuration: {}", message),
+            Self::OpenCV(error) => write!(f, "opencv error: {}", error),
+            Self::Io(error) => write!(f, "io error: {}", error),
+            Self::Json(error) => write!(f, "json error: {}", error),
+            Self::WorkerPool(message) => write!(f, "worker pool error: {}", message),
+        }
+    }
+}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::OpenCV(error) => Some(error),
+            Self::Io(error) => Some(error),
+            Self::Json(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+impl From<opencv::Error> for Error {
+    fn from(error: opencv::Error) -> Self {
+        Self::OpenCV(error)
+    }
+}
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
+
+/// oics 的结果类型，错误类型默认为 `Error`
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// 图像为空时返回 `Error::EmptyImage`
+pub(crate) fn check_not_empty(mat: &Mat) -> Result<()> {
+    if mat.rows() <= 0 || mat.cols() <= 0 {
+        return Err(Error::EmptyImage);
+    }
+
+    Ok(())
+}
+
+/// 参数不为正数时返回 `Error::InvalidParameter`
+pub(crate) fn check_positive(name: &str, value: f64) -> Result<()> {
+    if value.is_nan() || value <= 0.0 {
+        return Err(Error::InvalidParameter(format!(
+            "{} must be positive, got {}",
+            name, value
+        )));
+    }
+
+    Ok(())
+}
+
+/// 检查角度搜索范围，返回单侧的角度数目
+///
+/// 角度步长不为正数，或最大偏转角度与角度步长之比超出 `u16` 的范围时返回 `Error::InvalidParameter`
+pub(crate) fn check_angle_range(name: &str, max_angle: u16, step: f64) -> Result<u16> {
+    check_positive(&format!("{} angle step", name), step)?;
+    let angle_count = max_angle as f64 / step;
+    if angle_count > u16::MAX as f64 {
+        return Err(Error::InvalidParameter(format!(
+            "{} max angle / angle step must not exceed {}, got {} / {}",
+            name,
+            u16::MAX,
+            max_angle,
+            step
+        )));
+    }
+
+    Ok(angle_count as u16)
+}
@@ -1,8 +1,9 @@
 // cSpell: disable
 use opencv::{
     core::{
-        dft, log, magnitude, merge, min_max_loc, no_array, split, Mat, Point, Rect, Scalar, Vec4i,
-        CV_32F, CV_8UC1, DFT_COMPLEX_INPUT, DFT_COMPLEX_OUTPUT, DFT_SCALE,
+        copy_make_border, dft, get_optimal_dft_size, log, magnitude, merge, min_max_loc, multiply,
+        no_array, split, Mat, Point, Rect, Scalar, Vec4i, BORDER_CONSTANT, CV_32F, CV_8UC1,
+        DFT_COMPLEX_INPUT, DFT_COMPLEX_OUTPUT, DFT_SCALE,
     },
     imgcodecs,
     imgproc::{self, canny, cvt_color, hough_lines_p},
@@ -11,35 +12,77 @@ use opencv::{
 };
 use std::f64::consts::PI;
 
-use crate::transfer::TransformableMatrix;
+use crate::{
+    calculate,
+    error::{self, Error, Result},
+    omr::{OmrResult, ResultStatus},
+    transfer::TransformableMatrix,
+    types::PeakInterpolation,
+};
+
+/// 频谱角度积分时忽略的中心低频区域半径，相对于最大积分半径的比例
+const SPECTRUM_MIN_RADIUS_RATIO: f64 = 0.1;
 
 fn new_mat() -> Mat {
-    Mat::zeros(0, 0, CV_32F).unwrap().to_mat().unwrap()
+    Mat::default()
 }
 
-fn mul_image(image: &Mat, mul: f64) -> opencv::Result<Mat> {
+fn mul_image(image: &Mat, mul: f64) -> Result<Mat> {
     let mut clone: Mat = image.clone();
     image.convert_to(&mut clone, CV_32F, mul, 0.0)?;
     Ok(clone)
 }
 
-fn mul_add_image(image: &Mat, mul: f64, add: f64) -> opencv::Result<Mat> {
+fn mul_add_image(image: &Mat, mul: f64, add: f64) -> Result<Mat> {
     let mut clone = image.clone();
     image.convert_to(&mut clone, CV_32F, mul, add)?;
     Ok(clone)
 }
 
-pub fn rev(filter: &Mat) -> opencv::Result<Mat> {
+pub fn rev(filter: &Mat) -> Result<Mat> {
     mul_add_image(&mul_add_image(filter, -1.0, 0.0)?, 1.0, 1.0)
 }
 
-fn log_image(image: &Mat) -> opencv::Result<Mat> {
+fn log_image(image: &Mat) -> Result<Mat> {
     let mut clone = image.clone();
     log(&image, &mut clone)?;
     Ok(clone)
 }
 
-fn fft_complex(image: &Mat) -> opencv::Result<(Mat, Mat)> {
+/// ### 为傅里叶变换准备图像
+///
+/// 先乘以汉宁窗，抑制图像边界不连续在频谱中产生的十字亮线，
+/// 再在右侧与下方补零至 `get_optimal_dft_size` 给出的正方形尺寸。
+/// 频谱为正方形时各方向的频率分辨率一致，频谱中的角度与图像中的角度相对应
+fn get_dft_input(image: &Mat) -> Result<Mat> {
+    let windowed = if image.rows() > 1 && image.cols() > 1 {
+        let mut window = Mat::default();
+        imgproc::create_hanning_window(&mut window, image.size()?, CV_32F)?;
+        let mut windowed = Mat::default();
+        multiply(image, &window, &mut windowed, 1.0, -1)?;
+        windowed
+    } else {
+        image.clone()
+    };
+
+    let dft_size = get_optimal_dft_size(image.rows().max(image.cols()))?;
+    let mut padded = Mat::default();
+    copy_make_border(
+        &windowed,
+        &mut padded,
+        0,
+        dft_size - image.rows(),
+        0,
+        dft_size - image.cols(),
+        BORDER_CONSTANT,
+        Scalar::all(0.0),
+    )?;
+
+    Ok(padded)
+}
+
+fn fft_complex(image: &Mat) -> Result<(Mat, Mat)> {
+    let image = get_dft_input(image)?;
     let vec_of_mat = VectorOfMat::from(vec![
         image.clone(),
         Mat::zeros(image.rows(), image.cols(), CV_32F)?.to_mat()?,
@@ -64,28 +107,47 @@ fn fft_complex(image: &Mat) -> opencv::Result<(Mat, Mat)> {
     ))
 }
 
-fn fft_shift(image: &Mat) -> opencv::Result<Mat> {
-    let clone = image.clone();
-    let cx = image.cols() / 2;
-    let cy = image.rows() / 2;
-    let mut q0 = Mat::roi(&clone, Rect::new(0, 0, cx, cy))?;
-    let mut q1 = Mat::roi(&clone, Rect::new(cx, 0, cx, cy))?;
-    let mut q2 = Mat::roi(&clone, Rect::new(0, cy, cx, cy))?;
-    let mut q3 = Mat::roi(&clone, Rect::new(cx, cy, cx, cy))?;
-
-    let mut tmp = q0.clone();
-    q0.copy_to(&mut tmp)?;
-    q3.copy_to(&mut q0)?;
-    tmp.copy_to(&mut q3)?;
-
-    q1.copy_to(&mut tmp)?;
-    q2.copy_to(&mut q1)?;
-    tmp.copy_to(&mut q2)?;
+/// ### 将零频移至频谱中心
+///
+/// 零频移至 (`cols / 2`, `rows / 2`) 处，与 `numpy.fft.fftshift` 一致，
+/// 尺寸为奇数时同样不会丢失行或列
+pub(crate) fn fft_shift(image: &Mat) -> Result<Mat> {
+    let (cols, rows) = (image.cols(), image.rows());
+    // 源图像中零频所在的左上象限尺寸
+    let (left, top) = (cols - cols / 2, rows - rows / 2);
+    let (right, bottom) = (cols - left, rows - top);
+
+    let shifted = Mat::zeros(rows, cols, image.typ())?.to_mat()?;
+    let quadrants = [
+        (
+            Rect::new(0, 0, left, top),
+            Rect::new(right, bottom, left, top),
+        ),
+        (
+            Rect::new(left, 0, right, top),
+            Rect::new(0, bottom, right, top),
+        ),
+        (
+            Rect::new(0, top, left, bottom),
+            Rect::new(right, 0, left, bottom),
+        ),
+        (
+            Rect::new(left, top, right, bottom),
+            Rect::new(0, 0, right, bottom),
+        ),
+    ];
+    for (src_rect, dst_rect) in quadrants.iter() {
+        if src_rect.width == 0 || src_rect.height == 0 {
+            continue;
+        }
+        let mut dst_roi = Mat::roi(&shifted, *dst_rect)?;
+        Mat::roi(image, *src_rect)?.copy_to(&mut dst_roi)?;
+    }
 
-    Ok(clone)
+    Ok(shifted)
 }
 
-fn correction(image: &Mat) -> opencv::Result<Mat> {
+fn correction(image: &Mat) -> Result<Mat> {
     let mut min = 0.0;
     let mut max = 0.0;
     min_max_loc(
@@ -105,15 +167,15 @@ fn correction(image: &Mat) -> opencv::Result<Mat> {
     Ok(clone2)
 }
 
-fn fft_magnitude(fft: &(Mat, Mat)) -> opencv::Result<Mat> {
+fn fft_magnitude(fft: &(Mat, Mat)) -> Result<Mat> {
     let mut image_magnitude = new_mat();
     magnitude(&fft.0, &fft.1, &mut image_magnitude)?;
-    let image: Mat = (correction(&image_magnitude) as opencv::Result<Mat>)?;
+    let image: Mat = (correction(&image_magnitude) as Result<Mat>)?;
     let image: Mat = mul_image(&image, 255.0)?;
     Ok(image)
 }
 
-fn fft_magnitude_log(fft: &(Mat, Mat)) -> opencv::Result<Mat> {
+fn fft_magnitude_log(fft: &(Mat, Mat)) -> Result<Mat> {
     let image_magnitude = fft_magnitude(&fft)? as Mat;
     let image = mul_add_image(&image_magnitude, 1.0, 1.0 / 255.0)? as Mat;
     let image = &log_image(&image)? as &Mat;
@@ -121,7 +183,9 @@ fn fft_magnitude_log(fft: &(Mat, Mat)) -> opencv::Result<Mat> {
     Ok(image)
 }
 
-pub fn get_fft_image(gray_tm: &TransformableMatrix) -> opencv::Result<(Mat, Mat)> {
+pub fn get_fft_image(gray_tm: &TransformableMatrix) -> Result<(Mat, Mat)> {
+    error::check_not_empty(gray_tm.get_mat())?;
+
     let image_file = {
         let gray_tm_mat = gray_tm.get_mat();
         let mut clone = gray_tm_mat.clone();
@@ -142,6 +206,7 @@ pub fn get_fft_image(gray_tm: &TransformableMatrix) -> opencv::Result<(Mat, Mat)
 
 /// ### 利用傅里叶变换查找偏转角
 ///
+/// 频谱中未检测到可投票的直线时返回 `Error::NoFeatures`
 pub fn get_angle_with_fft(
     // img_src: &str,
     gray_tm: &TransformableMatrix,
@@ -151,10 +216,45 @@ pub fn get_angle_with_fft(
     max_line_gap: f64,
     file_name: &str,
     edge_image_output_dir: &str,
-) -> Result<f64, opencv::Error> {
+) -> Result<f64> {
+    let result = get_result_with_fft(
+        gray_tm,
+        canny_threshold_1,
+        canny_threshold_2,
+        min_line_length,
+        max_line_gap,
+        Some(&(String::from(edge_image_output_dir) + file_name)),
+    )?;
+    if result.status == ResultStatus::NotAResult {
+        return Err(Error::NoFeatures(String::from(
+            "no lines found in the fft spectrum",
+        )));
+    }
+
+    Ok(result.angle)
+}
+
+/// ### 利用傅里叶变换查找偏转角，并以 `OmrResult` 的形式返回
+///
+/// **参数列表：**
+/// - `gray_tm`: 包含灰度图的 `TransformableMatrix`
+/// - `canny_threshold_1`: canny 边缘检测的弱阈值
+/// - `canny_threshold_2`: canny 边缘检测的强阈值
+/// - `min_line_length`: 感知的最小线段长度
+/// - `max_line_gap`: 感知的线段最大中断长度
+/// - `lined_image_output_path`: 直线图的输出路径，为 `None` 时不输出
+///
+pub fn get_result_with_fft(
+    gray_tm: &TransformableMatrix,
+    canny_threshold_1: f64,
+    canny_threshold_2: f64,
+    min_line_length: f64,
+    max_line_gap: f64,
+    lined_image_output_path: Option<&str>,
+) -> Result<OmrResult> {
     let fft_image = {
         #[allow(unused_variables)]
-        let (magnitude_image, magnitude_log_image) = get_fft_image(gray_tm).unwrap();
+        let (magnitude_image, magnitude_log_image) = get_fft_image(gray_tm)?;
         magnitude_log_image
     };
 
@@ -170,10 +270,6 @@ pub fn get_angle_with_fft(
         false,
     )?;
 
-    // 直线图
-    let mut lined_img = Mat::default();
-    cvt_color(&edges, &mut lined_img, imgproc::COLOR_GRAY2BGR, 0)?;
-
     // 在边缘图像中检测直线
     let mut lines = Mat::default();
     let rho = 1.0;
@@ -194,6 +290,7 @@ pub fn get_angle_with_fft(
 
     let mut average_angle = 0.0;
     let mut max_votes = 0;
+    let mut angles = Vec::with_capacity(lines.rows() as usize);
     for i in 0..lines.rows() {
         let line = lines.at_row::<Vec4i>(i)?[0];
         let x1 = line[0] as f64;
@@ -201,39 +298,27 @@ pub fn get_angle_with_fft(
         let x2 = line[2] as f64;
         let y2 = line[3] as f64;
 
-        // 画线
-        imgproc::line(
-            &mut lined_img,
-            Point::new(line[0], line[1]),
-            Point::new(line[2], line[3]),
-            Scalar::new(186.0, 88.0, 255.0, 0.0),
-            1,
-            imgproc::LINE_AA,
-            0,
-        )?;
-
-        let angle = {
-            let counted_angle = ((y2 - y1).atan2(x2 - x1) * 180.0) as f64 / PI;
-            if counted_angle < -45.0 {
-                counted_angle + 90.0
-            } else if counted_angle > 45.0 {
-                counted_angle - 90.0
-            } else {
-                counted_angle
-            }
-        };
+        // 限制偏转角度在 -45deg ~ +45deg 之间
+        let angle = calculate::fold_line_angle(
+            (y2 - y1).atan2(x2 - x1) * 180.0 / PI,
+            calculate::DEFAULT_MIN_LINE_ANGLE,
+        );
+        angles.push(angle);
         // 计算数据分布概率密度（投票）
         let mut votes = 0;
         for j in 0..lines.rows() {
             if i == j {
                 continue;
             }
-            let line_j = lines.at_row::<Vec4i>(i)?[0];
+            let line_j = lines.at_row::<Vec4i>(j)?[0];
             let x1_j = line_j[0] as f64;
             let y1_j = line_j[1] as f64;
             let x2_j = line_j[2] as f64;
             let y2_j = line_j[3] as f64;
-            let angle_j = ((y2_j - y1_j).atan2(x2_j - x1_j) * 180.0) / PI;
+            let angle_j = calculate::fold_line_angle(
+                (y2_j - y1_j).atan2(x2_j - x1_j) * 180.0 / PI,
+                calculate::DEFAULT_MIN_LINE_ANGLE,
+            );
             let distance = (angle_j - angle).abs();
             if distance < 0.1 {
                 votes += 1;
@@ -245,12 +330,200 @@ pub fn get_angle_with_fft(
         }
     }
 
-    imgcodecs::imwrite(
-        &(String::from(edge_image_output_dir) + file_name),
-        &lined_img,
-        &VectorOfi32::from(vec![imgcodecs::IMWRITE_JPEG_QUALITY, 100]),
-    )
-    .unwrap();
+    if let Some(output_path) = lined_image_output_path {
+        // 直线图
+        let mut lined_img = Mat::default();
+        cvt_color(&edges, &mut lined_img, imgproc::COLOR_GRAY2BGR, 0)?;
+
+        // 画线
+        for i in 0..lines.rows() {
+            let line = lines.at_row::<Vec4i>(i)?[0];
+            imgproc::line(
+                &mut lined_img,
+                Point::new(line[0], line[1]),
+                Point::new(line[2], line[3]),
+                Scalar::new(186.0, 88.0, 255.0, 0.0),
+                1,
+                imgproc::LINE_AA,
+                0,
+            )?;
+        }
+
+        imgcodecs::imwrite(
+            output_path,
+            &lined_img,
+            &VectorOfi32::from(vec![imgcodecs::IMWRITE_JPEG_QUALITY, 100]),
+        )?;
+    }
+
+    // 根据直线角度的一致程度计算置信度与估计误差
+    let (confidence, uncertainty) = calculate::get_angles_agreement(&angles, average_angle, 0.1);
+
+    Ok(OmrResult {
+        angle: average_angle,
+        status: if max_votes > 0 {
+            ResultStatus::Believed
+        } else {
+            ResultStatus::NotAResult
+        },
+        candidates: vec![average_angle],
+        confidence,
+        uncertainty,
+    })
+}
+
+/// 对频谱进行双线性插值采样，坐标超出范围时返回 `None`
+fn sample_spectrum(spectrum: &[f64], width: usize, height: usize, x: f64, y: f64) -> Option<f64> {
+    if x < 0.0 || y < 0.0 || x > (width - 1) as f64 || y > (height - 1) as f64 {
+        return None;
+    }
+
+    let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (dx, dy) = (x - x0 as f64, y - y0 as f64);
+    let top = spectrum[y0 * width + x0] * (1.0 - dx) + spectrum[y0 * width + x1] * dx;
+    let bottom = spectrum[y1 * width + x0] * (1.0 - dx) + spectrum[y1 * width + x1] * dx;
+
+    Some(top * (1.0 - dy) + bottom * dy)
+}
+
+/// ### 计算频谱沿各方向射线的平均能量
+///
+/// **参数列表：**
+/// - `spectrum`: 按行排列、低频位于中心的幅度谱
+/// - `width`: 幅度谱宽度
+/// - `height`: 幅度谱高度
+/// - `angles`: 直线角度（deg），图像坐标系下 y 轴向下
+///
+/// 角度为 θ 的直线在频谱中表现为方向 θ + 90deg 的亮线，表格的竖线则对应方向 θ，
+/// 因此对每个角度累加从中心出发、方向为 θ + k * 90deg 的四条射线上的能量。
+/// 射线从 `SPECTRUM_MIN_RADIUS_RATIO` 处开始积分以排除中心低频区域，
+/// 积分半径不超过频谱内切圆，使各方向的采样点数一致
+pub fn get_spectrum_angle_energies(
+    spectrum: &[f64],
+    width: usize,
+    height: usize,
+    angles: &[f64],
+) -> Vec<f64> {
+    let (center_x, center_y) = ((width / 2) as f64, (height / 2) as f64);
+    let max_radius = (width.min(height) / 2) as f64 - 1.0;
+    let min_radius = (max_radius * SPECTRUM_MIN_RADIUS_RATIO).max(2.0);
+    if max_radius <= min_radius {
+        return vec![0.0; angles.len()];
+    }
+
+    angles
+        .iter()
+        .map(|angle| {
+            let mut energy = 0.0;
+            let mut counts = 0u32;
+            for quarter in 0..4 {
+                let radian = (angle + quarter as f64 * calculate::LINE_ANGLE_PERIOD).to_radians();
+                let (sin, cos) = radian.sin_cos();
+                let mut radius = min_radius;
+                while radius <= max_radius {
+                    let x = center_x + radius * cos;
+                    let y = center_y + radius * sin;
+                    if let Some(value) = sample_spectrum(spectrum, width, height, x, y) {
+                        energy += value;
+                        counts += 1;
+                    }
+                    radius += 1.0;
+                }
+            }
+
+            if counts == 0 {
+                0.0
+            } else {
+                energy / counts as f64
+            }
+        })
+        .collect()
+}
+
+/// ### 生成频谱极坐标投影的候选角度
+///
+/// 候选角度为 `[-max_angle, max_angle)` 区间内 `angle_step` 的整数倍。
+/// 各角度累加 θ + k * 90deg 四个方向的能量，`max_angle` 为 45 时 ±45deg 对应同一组射线，
+/// 只保留 -45deg 以免同一方向成为两个并列的候选
+pub fn get_spectrum_candidate_angles(max_angle: u16, angle_step: f64) -> Vec<f64> {
+    let range_max_index = (max_angle as f64 / angle_step) as i32;
+    (-range_max_index..range_max_index)
+        .map(|index| index as f64 * angle_step)
+        .collect()
+}
+
+/// ### 利用频谱的极坐标投影查找偏转角
+///
+/// 对 `get_fft_image` 输出的对数幅度谱，沿从中心出发的各方向射线积分能量，
+/// 取能量最大的方向作为主方向，不依赖 canny 与霍夫变换参数
+///
+/// **参数列表：**
+/// - `gray_tm`: 包含灰度图的 `TransformableMatrix`
+/// - `max_angle`: 最大偏转角度
+/// - `angle_step`: 角度步长
+/// - `peak_interpolation`: 峰值插值方式
+///
+pub fn get_result_with_fft_spectrum(
+    gray_tm: &TransformableMatrix,
+    max_angle: u16,
+    angle_step: f64,
+    peak_interpolation: PeakInterpolation,
+) -> Result<OmrResult> {
+    error::check_positive("angle step", angle_step)?;
+
+    let fft_image = {
+        #[allow(unused_variables)]
+        let (magnitude_image, magnitude_log_image) = get_fft_image(gray_tm)?;
+        magnitude_log_image
+    };
+
+    let (width, height) = (fft_image.cols() as usize, fft_image.rows() as usize);
+    let mut spectrum = Vec::with_capacity(width * height);
+    for row_index in 0..fft_image.rows() {
+        spectrum.extend(
+            fft_image
+                .at_row::<u8>(row_index)?
+                .iter()
+                .map(|value| *value as f64),
+        );
+    }
+
+    let angles = get_spectrum_candidate_angles(max_angle, angle_step);
+    let energies = get_spectrum_angle_energies(&spectrum, width, height, &angles);
+
+    let max_energy = energies.iter().cloned().fold(f64::MIN, f64::max);
+    let candidates: Vec<usize> = (0..energies.len())
+        .filter(|index| energies[*index] == max_energy)
+        .collect();
+
+    if candidates.is_empty() || candidates.len() == energies.len() {
+        // 各方向能量相同，如空白图像或图像过小
+        return Ok(OmrResult {
+            angle: 0.0,
+            status: ResultStatus::NotAResult,
+            candidates: vec![],
+            confidence: 0.0,
+            uncertainty: max_angle as f64,
+        });
+    }
+
+    let most_possible_index = candidates[0];
+    let offset = if candidates.len() == 1 {
+        calculate::get_peak_offset(&energies, most_possible_index, peak_interpolation)
+    } else {
+        0.0
+    };
 
-    Ok(average_angle)
+    Ok(OmrResult {
+        angle: angles[most_possible_index] + offset * angle_step,
+        status: if candidates.len() == 1 {
+            ResultStatus::Believed
+        } else {
+            ResultStatus::NeedCheck
+        },
+        candidates: candidates.iter().map(|index| angles[*index]).collect(),
+        confidence: calculate::get_peak_confidence(&energies, most_possible_index),
+        uncertainty: calculate::get_peak_half_width(&energies, most_possible_index) * angle_step,
+    })
 }
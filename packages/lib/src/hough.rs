@@ -1,4 +1,9 @@
-use crate::transfer::TransformableMatrix;
+use crate::{
+    calculate,
+    error::{self, Error, Result},
+    omr::{OmrResult, ResultStatus},
+    transfer::TransformableMatrix,
+};
 use opencv::{
     core::{Point, Point2f, Scalar},
     imgcodecs,
@@ -14,14 +19,43 @@ use opencv::{
 /// - `min_line_length`: 感知的最小线段长度
 /// - `max_line_gap`: 感知的线段最大中断长度
 ///
+/// 未检测到线段时返回 `Error::NoFeatures`
 pub fn get_angle_with_hough(
     gray_tm: &TransformableMatrix,
     min_line_length: f64,
     max_line_gap: f64,
     file_name: &str,
     edge_image_output_dir: &str,
-) -> Result<f64, opencv::Error> {
+) -> Result<f64> {
+    let result = get_result_with_hough(
+        gray_tm,
+        min_line_length,
+        max_line_gap,
+        Some(&(String::from(edge_image_output_dir) + file_name)),
+    )?;
+    if result.status == ResultStatus::NotAResult {
+        return Err(Error::NoFeatures(String::from("no lines found by hough")));
+    }
+
+    Ok(result.angle)
+}
+
+/// ### 利用霍夫变换查找偏转角，并以 `OmrResult` 的形式返回
+///
+/// **参数列表：**
+/// - `gray_tm`: 包含灰度图的 `TransformableMatrix`
+/// - `min_line_length`: 感知的最小线段长度
+/// - `max_line_gap`: 感知的线段最大中断长度
+/// - `lined_image_output_path`: 直线图的输出路径，为 `None` 时不输出
+///
+pub fn get_result_with_hough(
+    gray_tm: &TransformableMatrix,
+    min_line_length: f64,
+    max_line_gap: f64,
+    lined_image_output_path: Option<&str>,
+) -> Result<OmrResult> {
     let mat = gray_tm.get_mat();
+    error::check_not_empty(mat)?;
 
     let mut edges = Mat::default();
     canny(mat, &mut edges, 50.0, 150.0, 3, false)?;
@@ -41,60 +75,87 @@ pub fn get_angle_with_hough(
         max_line_gap,
     )?;
 
-    // 直线图
-    let mut lined_img = Mat::default();
-    cvt_color(&edges, &mut lined_img, imgproc::COLOR_GRAY2BGR, 0)?;
+    let result = get_result_from_lines(&lines);
+
+    if let Some(output_path) = lined_image_output_path {
+        // 直线图
+        let mut lined_img = Mat::default();
+        cvt_color(&edges, &mut lined_img, imgproc::COLOR_GRAY2BGR, 0)?;
 
-    // 获取直线的斜率
+        // 画线
+        for l in lines.iter() {
+            line(
+                &mut lined_img,
+                Point::new(l[0] as i32, l[1] as i32),
+                Point::new(l[2] as i32, l[3] as i32),
+                Scalar::new(186.0, 88.0, 255.0, 0.0),
+                1,
+                imgproc::LINE_AA,
+                0,
+            )?;
+        }
+
+        imgcodecs::imwrite(
+            output_path,
+            &lined_img,
+            &VectorOfi32::from(vec![imgcodecs::IMWRITE_JPEG_QUALITY, 100]),
+        )?;
+    }
+
+    Ok(result)
+}
+
+/// 相邻峰值权重之比不低于该值时，认为存在多个同等可信的角度
+const HISTOGRAM_AMBIGUOUS_PEAK_RATIO: f64 = 0.95;
+
+/// ### 根据霍夫变换检测到的线段查找偏转角
+///
+/// 以线段长度为权重统计角度直方图，经平滑后取最高峰作为偏转角，其余峰值作为候选角度
+///
+/// **参数列表：**
+/// - `lines`: 霍夫概率变换输出的线段
+///
+pub fn get_result_from_lines(lines: &VectorOfVec4f) -> OmrResult {
+    // 获取直线的斜率与长度
     let mut angles = vec![];
+    let mut lengths = vec![];
     for l in lines.iter() {
         let pt1 = Point2f::new(l[0], l[1]);
         let pt2 = Point2f::new(l[2], l[3]);
 
-        // 画线
-        line(
-            &mut lined_img,
-            Point::new(l[0] as i32, l[1] as i32),
-            Point::new(l[2] as i32, l[3] as i32),
-            Scalar::new(186.0, 88.0, 255.0, 0.0),
-            1,
-            imgproc::LINE_AA,
-            0,
-        )?;
-
-        let mut angle = (pt2.y - pt1.y).atan2(pt2.x - pt1.x) * 180.0 / std::f32::consts::PI;
+        let angle = (pt2.y - pt1.y).atan2(pt2.x - pt1.x) as f64 * 180.0 / std::f64::consts::PI;
         // 限制偏转角度在 -45deg ~ +45deg 之间
-        angle = angle % 45.0;
-        angles.push(angle);
+        angles.push(calculate::fold_line_angle(
+            angle,
+            calculate::DEFAULT_MIN_LINE_ANGLE,
+        ));
+        lengths.push(((pt2.x - pt1.x).powf(2.0) + (pt2.y - pt1.y).powf(2.0)).sqrt() as f64);
     }
 
-    // 找到最常出现的斜率作为图像的旋转角度
-    let range = 0.1;
-    // 目标角度
-    let mut target_angle = angles[0];
-    // 处于目标角度的直线数目
-    let mut target_angle_lines_count = 0;
-    // 找寻目标角度线段总长度最大的角度作为最终的偏转角度
-    for i in 0..angles.len() {
-        let mut count = 0;
-        for j in 0..angles.len() {
-            if (angles[i] - angles[j]).abs() < range {
-                count += 1;
-            }
-        }
-        if count > target_angle_lines_count {
-            target_angle = angles[i];
-            target_angle_lines_count = count;
-        }
-    }
+    // 找寻线段总长度最大的角度作为最终的偏转角度
+    let histogram = calculate::get_angle_histogram(&angles, &lengths, 0.1, 2, 3);
 
-    imgcodecs::imwrite(
-        &(String::from(edge_image_output_dir) + file_name),
-        &lined_img,
-        &VectorOfi32::from(vec![imgcodecs::IMWRITE_JPEG_QUALITY, 100]),
-    )
-    .unwrap();
-
-    // 返回旋转角度 target_angle
-    Ok(target_angle as f64)
+    match histogram.peaks.first() {
+        Some(peak) => OmrResult {
+            angle: peak.angle,
+            status: match histogram.peaks.get(1) {
+                Some(second_peak)
+                    if second_peak.weight >= peak.weight * HISTOGRAM_AMBIGUOUS_PEAK_RATIO =>
+                {
+                    ResultStatus::NeedCheck
+                }
+                _ => ResultStatus::Believed,
+            },
+            candidates: histogram.peaks.iter().map(|peak| peak.angle).collect(),
+            confidence: histogram.confidence,
+            uncertainty: histogram.uncertainty,
+        },
+        None => OmrResult {
+            angle: 0.0,
+            status: ResultStatus::NotAResult,
+            candidates: vec![],
+            confidence: 0.0,
+            uncertainty: histogram.uncertainty,
+        },
+    }
 }
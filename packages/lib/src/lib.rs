@@ -2,15 +2,26 @@ pub use opencv::{
     core, highgui, imgcodecs, imgproc, prelude, types as opencv_types, Result as OpenCV_Result,
 };
 
+pub mod anchor;
 pub mod calculate;
 pub mod constants;
+pub mod detector;
+pub mod error;
 pub mod fft;
 pub mod hough;
 pub mod omr;
+pub mod orientation;
+pub mod pipeline;
+pub mod pool;
 pub mod projection;
+pub mod radon;
+pub mod score;
+pub mod template;
 pub mod transfer;
 pub mod types;
 
+pub use error::{Error, Result};
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -19,13 +30,42 @@ mod tests {
         types::{ImageFormat, RotateClipStrategy},
     };
     use opencv::{
-        core::{Scalar, BORDER_CONSTANT},
+        core::{Mat, Point, Scalar, BORDER_CONSTANT, CV_8UC3},
         imgcodecs, imgproc,
     };
     use rand::Rng;
     use std::{io::Write, path::Path};
 
     const DATA_SET_DIR_PATH: &str = "../../dataset/dataset";
+
+    /// 绘制含有若干水平线的图像，并旋转至偏转角为 `angle`
+    fn get_lined_image(angle: f64) -> TransformableMatrix {
+        let mut lined_mat =
+            Mat::new_rows_cols_with_default(240, 320, CV_8UC3, Scalar::all(255.0)).unwrap();
+        for y in (40..200).step_by(20) {
+            imgproc::line(
+                &mut lined_mat,
+                Point::new(30, y),
+                Point::new(290, y),
+                Scalar::all(0.0),
+                3,
+                imgproc::LINE_8,
+                0,
+            )
+            .unwrap();
+        }
+
+        transfer::rotate_mat(
+            &TransformableMatrix::from_matrix(&lined_mat),
+            -angle,
+            1.0,
+            imgproc::INTER_LINEAR,
+            BORDER_CONSTANT,
+            Scalar::new(255.0, 255.0, 255.0, 0.0),
+            RotateClipStrategy::CONTAIN,
+        )
+        .unwrap()
+    }
     #[allow(dead_code)]
     // #[test]
     fn crate_omr_correct_default_test() {
@@ -77,29 +117,31 @@ mod tests {
                 dst
             });
 
-            original_image
-                .im_write("./tmp.jpg", ImageFormat::JPEG, 100)
-                .unwrap();
-
             let algorithm_start = instant.elapsed().as_millis();
 
-            let (result_angle, need_check) = omr::correct_default(
-                &"./tmp.jpg",
-                Path::new("../../dataset/result/projection")
-                    .join(file_name)
-                    .to_str()
-                    .unwrap(),
-                45,
-                0.2,
-                248,
-                230,
-                150.0,
-                50.0,
-            )
-            .unwrap();
+            let omr::CorrectedImage {
+                image: corrected_image,
+                result:
+                    omr::CorrectionResult {
+                        angle: result_angle,
+                        need_check,
+                        ..
+                    },
+            } = omr::correct_matrix(&original_image, &omr::CorrectionOptions::default()).unwrap();
 
             let algorithm_end = instant.elapsed().as_millis();
 
+            corrected_image
+                .im_write(
+                    Path::new("../../dataset/result/projection")
+                        .join(file_name)
+                        .to_str()
+                        .unwrap(),
+                    ImageFormat::JPEG,
+                    100,
+                )
+                .unwrap();
+
             if !need_check {
                 if (random_angle - result_angle).abs() >= 0.4 {
                     println!("{}", (random_angle - result_angle).abs());
@@ -183,29 +225,32 @@ mod tests {
                     dst
                 });
 
-                original_image
-                    .im_write("./tmp.jpg", ImageFormat::JPEG, 100)
-                    .unwrap();
-
                 let algorithm_start = instant.elapsed().as_millis();
 
-                let (result_angle, need_check) = omr::correct_default(
-                    &"./tmp.jpg",
-                    Path::new("../../dataset/result/projection")
-                        .join(file_name)
-                        .to_str()
-                        .unwrap(),
-                    45,
-                    0.2,
-                    248,
-                    230,
-                    150.0,
-                    50.0,
-                )
-                .unwrap();
+                let omr::CorrectedImage {
+                    image: corrected_image,
+                    result:
+                        omr::CorrectionResult {
+                            angle: result_angle,
+                            need_check,
+                            ..
+                        },
+                } = omr::correct_matrix(&original_image, &omr::CorrectionOptions::default())
+                    .unwrap();
 
                 let algorithm_end = instant.elapsed().as_millis();
 
+                corrected_image
+                    .im_write(
+                        Path::new("../../dataset/result/projection")
+                            .join(file_name)
+                            .to_str()
+                            .unwrap(),
+                        ImageFormat::JPEG,
+                        100,
+                    )
+                    .unwrap();
+
                 let distance = (original_image_rotate_angle - result_angle).abs();
 
                 test_log
@@ -244,6 +289,2062 @@ mod tests {
         );
     }
 
+    mod anchor {
+        use crate::{
+            anchor::{self, AnchorDetector},
+            transfer::TransformableMatrix,
+        };
+        use opencv::{
+            core::{Mat, Point2f, Rect, Scalar, CV_8UC3},
+            imgproc,
+            prelude::MatTraitConst,
+        };
+
+        /// 在 400 x 500 的图像中绘制实心方块，`centers` 为方块中心
+        fn get_anchored_image(centers: &[(i32, i32)]) -> TransformableMatrix {
+            let mut mat =
+                Mat::new_rows_cols_with_default(500, 400, CV_8UC3, Scalar::all(255.0)).unwrap();
+            for (x, y) in centers {
+                imgproc::rectangle(
+                    &mut mat,
+                    Rect::new(x - 10, y - 10, 20, 20),
+                    Scalar::all(0.0),
+                    imgproc::FILLED,
+                    imgproc::LINE_8,
+                    0,
+                )
+                .unwrap();
+            }
+            // 比左上角定位块更靠近角点的长条，边长比过大，不是定位块
+            imgproc::rectangle(
+                &mut mat,
+                Rect::new(10, 5, 60, 10),
+                Scalar::all(0.0),
+                imgproc::FILLED,
+                imgproc::LINE_8,
+                0,
+            )
+            .unwrap();
+
+            TransformableMatrix::from_matrix(&mat)
+        }
+
+        fn assert_near(point: Point2f, x: f32, y: f32) {
+            assert!(
+                (point.x - x).abs() < 1.0 && (point.y - y).abs() < 1.0,
+                "{:?} ({}, {})",
+                point,
+                x,
+                y
+            );
+        }
+
+        #[test]
+        fn find_anchors_test() {
+            let detector = AnchorDetector::default();
+            let src = get_anchored_image(&[(40, 40), (360, 40), (360, 460), (40, 460)]);
+            let anchors = anchor::find_anchors(&src, &detector).unwrap().unwrap();
+            // 20 x 20 方块覆盖的像素为 [x - 10, x + 9]，中心偏移半个像素
+            assert_near(anchors.top_left, 39.5, 39.5);
+            assert_near(anchors.top_right, 359.5, 39.5);
+            assert_near(anchors.bottom_right, 359.5, 459.5);
+            assert_near(anchors.bottom_left, 39.5, 459.5);
+
+            // 缺少任一定位块时返回 None
+            let src = get_anchored_image(&[(40, 40), (360, 40), (360, 460)]);
+            assert!(anchor::find_anchors(&src, &detector).unwrap().is_none());
+        }
+
+        #[test]
+        fn warp_to_anchors_test() {
+            let detector = AnchorDetector::default();
+            let src = get_anchored_image(&[(40, 40), (360, 40), (360, 460), (40, 460)]);
+            let anchors = anchor::find_anchors(&src, &detector).unwrap().unwrap();
+
+            // 输出尺寸为定位块间距加两侧边距
+            let warped = anchor::warp_to_anchors(&src, &anchors, &detector).unwrap();
+            let size = warped.get_mat().size().unwrap();
+            assert_eq!((size.width, size.height), (320 + 40, 420 + 40));
+            // 变换后定位块中心位于距离图像边缘 output_margin 处
+            let warped_anchors = anchor::find_anchors(&warped, &detector).unwrap().unwrap();
+            assert_near(warped_anchors.top_left, 20.0, 20.0);
+            assert_near(warped_anchors.bottom_right, 340.0, 440.0);
+
+            // 指定输出尺寸
+            let detector = AnchorDetector {
+                output_width: 200,
+                output_height: 300,
+                output_margin: 10,
+                ..AnchorDetector::default()
+            };
+            let warped = anchor::correct_with_anchors(&src, &detector)
+                .unwrap()
+                .unwrap();
+            let size = warped.get_mat().size().unwrap();
+            assert_eq!((size.width, size.height), (220, 320));
+        }
+    }
+
+    mod calculate {
+        use crate::{calculate, types::PeakInterpolation, Error};
+
+        #[test]
+        fn arithmetic_mean_test() {
+            let values = [1.0, 2.0, 3.0, 6.0];
+            assert_eq!(calculate::get_arithmetic_mean(&values).unwrap(), 3.0);
+            let standard_deviation = calculate::get_standard_deviation(&values).unwrap();
+            assert!((standard_deviation - 3.5f64.sqrt()).abs() < 1e-9);
+
+            // 空数组返回错误而不是 panic
+            assert!(matches!(
+                calculate::get_arithmetic_mean(&[]),
+                Err(Error::InvalidParameter(_))
+            ));
+            assert!(matches!(
+                calculate::get_standard_deviation(&[]),
+                Err(Error::InvalidParameter(_))
+            ));
+            assert_eq!(calculate::get_peak_confidence(&[], 0), 0.0);
+        }
+
+        #[test]
+        fn peak_offset_test() {
+            // 顶点位于 x = 0.3 处的抛物线与高斯曲线
+            let parabola: Vec<f64> = (-3..=3)
+                .map(|x| 10.0 - (x as f64 - 0.3).powf(2.0))
+                .collect();
+            let gaussian: Vec<f64> = (-3..=3)
+                .map(|x| (-(x as f64 - 0.3).powf(2.0) / 2.0).exp())
+                .collect();
+
+            let offset = calculate::get_peak_offset(&parabola, 3, PeakInterpolation::PARABOLIC);
+            assert!((offset - 0.3).abs() < 1e-9, "{}", offset);
+            let offset = calculate::get_peak_offset(&gaussian, 3, PeakInterpolation::GAUSSIAN);
+            assert!((offset - 0.3).abs() < 1e-9, "{}", offset);
+            let offset = calculate::get_peak_offset(&parabola, 3, PeakInterpolation::NONE);
+            assert_eq!(offset, 0.0);
+            // 峰值位于曲线端点时不进行插值
+            let offset = calculate::get_peak_offset(&parabola, 0, PeakInterpolation::PARABOLIC);
+            assert_eq!(offset, 0.0);
+        }
+
+        #[test]
+        fn peak_confidence_test() {
+            // 主峰之外的次高峰为 2，均值为 9 / 7
+            let values = [0.0, 1.0, 5.0, 1.0, 0.0, 2.0, 0.0];
+            let confidence = calculate::get_peak_confidence(&values, 2);
+            assert!((confidence - 21.0 / 26.0).abs() < 1e-9, "{}", confidence);
+
+            // 单峰曲线置信度为 1，平坦曲线与等高双峰置信度为 0
+            assert_eq!(
+                calculate::get_peak_confidence(&[0.0, 1.0, 5.0, 1.0, 0.0], 2),
+                1.0
+            );
+            assert_eq!(calculate::get_peak_confidence(&[1.0, 1.0, 1.0], 1), 0.0);
+            assert_eq!(
+                calculate::get_peak_confidence(&[0.0, 5.0, 0.0, 5.0, 0.0], 1),
+                0.0
+            );
+            assert_eq!(calculate::get_peak_confidence(&values, 10), 0.0);
+        }
+
+        #[test]
+        fn peak_half_width_test() {
+            // 均值为 2，半高为 3，半高处的宽度为 2 个采样间隔
+            assert_eq!(
+                calculate::get_peak_half_width(&[0.0, 3.0, 4.0, 3.0, 0.0], 2),
+                1.0
+            );
+            // 尖峰至少为半个采样间隔
+            assert_eq!(
+                calculate::get_peak_half_width(&[0.0, 2.0, 4.0, 2.0, 0.0], 2),
+                0.5
+            );
+            // 越界时返回整条曲线的半宽
+            assert_eq!(
+                calculate::get_peak_half_width(&[0.0, 3.0, 4.0, 3.0, 0.0], 5),
+                2.5
+            );
+            assert_eq!(calculate::get_peak_half_width(&[], 0), 0.5);
+        }
+
+        #[test]
+        fn angles_agreement_test() {
+            let angles = [1.0, 1.2, 0.8, 5.0];
+            let (confidence, uncertainty) = calculate::get_angles_agreement(&angles, 1.0, 0.3);
+            assert_eq!(confidence, 0.75);
+            assert!(
+                (uncertainty - (0.08f64 / 3.0).sqrt()).abs() < 1e-9,
+                "{}",
+                uncertainty
+            );
+
+            // 标准差小于 range 的一半时取 range 的一半
+            let (confidence, uncertainty) = calculate::get_angles_agreement(&angles, 1.0, 1.0);
+            assert_eq!(confidence, 0.75);
+            assert_eq!(uncertainty, 0.5);
+
+            assert_eq!(
+                calculate::get_angles_agreement(&angles, 20.0, 0.3),
+                (0.0, 45.0)
+            );
+            assert_eq!(calculate::get_angles_agreement(&[], 0.0, 0.3), (0.0, 45.0));
+        }
+
+        #[test]
+        fn fold_line_angle_test() {
+            let min_angle = calculate::DEFAULT_MIN_LINE_ANGLE;
+            assert_eq!(calculate::fold_line_angle(50.0, min_angle), -40.0);
+            assert_eq!(calculate::fold_line_angle(-50.0, min_angle), 40.0);
+            assert_eq!(calculate::fold_line_angle(180.0 + 3.0, min_angle), 3.0);
+            assert_eq!(calculate::fold_line_angle(-93.0, min_angle), -3.0);
+            assert_eq!(calculate::fold_line_angle(45.0, min_angle), -45.0);
+            // 折叠至 [0, 90) 区间
+            assert_eq!(calculate::fold_line_angle(-10.0, 0.0), 80.0);
+        }
+
+        #[test]
+        fn angle_histogram_test() {
+            let angles = [1.02, 1.0, 0.98, 5.0, 5.01];
+            let weights = [100.0, 100.0, 100.0, 50.0, 50.0];
+            let histogram = calculate::get_angle_histogram(&angles, &weights, 0.1, 2, 3);
+
+            assert_eq!(histogram.peaks.len(), 2);
+            assert!((histogram.peaks[0].angle - 1.0).abs() < 1e-9);
+            assert!((histogram.peaks[1].angle - 5.005).abs() < 1e-9);
+            assert!(histogram.peaks[0].weight > histogram.peaks[1].weight);
+
+            let histogram = calculate::get_angle_histogram(&[], &[], 0.1, 2, 3);
+            assert!(histogram.peaks.is_empty());
+        }
+
+        #[test]
+        fn wrapped_angles_test() {
+            // 44.95° 与 -44.95° 为同一方向的直线，合并后的峰值高于 10° 处的峰值
+            let angles = [44.95, -44.95, 10.0];
+            let weights = [100.0, 100.0, 150.0];
+            let histogram = calculate::get_angle_histogram(&angles, &weights, 0.1, 2, 3);
+            assert_eq!(histogram.peaks.len(), 2);
+            assert!(
+                calculate::get_line_angle_distance(histogram.peaks[0].angle, 45.0) < 1e-9,
+                "{}",
+                histogram.peaks[0].angle
+            );
+            assert!((-45.0..45.0).contains(&histogram.peaks[0].angle));
+            assert!((histogram.peaks[1].angle - 10.0).abs() < 1e-9);
+
+            let (confidence, uncertainty) = calculate::get_angles_agreement(&angles, 45.0, 0.3);
+            assert!((confidence - 2.0 / 3.0).abs() < 1e-9);
+            assert_eq!(uncertainty, 0.15);
+            let (confidence, _) = calculate::get_angles_agreement(&angles, -44.95, 0.3);
+            assert!((confidence - 2.0 / 3.0).abs() < 1e-9);
+        }
+
+        #[test]
+        fn circular_weighted_median_test() {
+            assert!((calculate::get_line_angle_distance(44.9, -44.9) - 0.2).abs() < 1e-9);
+            let median =
+                calculate::get_circular_weighted_median(&[1.0, 1.1, 5.0], &[1.0, 1.0, 1.0]);
+            assert_eq!(median, Some(1.1));
+            // 权重足够大时偏离多数的角度成为中位数
+            let median =
+                calculate::get_circular_weighted_median(&[1.0, 1.1, 5.0], &[1.0, 1.0, 3.0]);
+            assert_eq!(median, Some(5.0));
+            // 跨越 ±45° 边界的角度视为相近
+            let median =
+                calculate::get_circular_weighted_median(&[44.9, -44.95, 10.0], &[1.0, 1.0, 1.0]);
+            assert_eq!(median, Some(44.9));
+            assert_eq!(calculate::get_circular_weighted_median(&[], &[]), None);
+        }
+    }
+
+    mod fft {
+        use crate::fft;
+        use opencv::{
+            core::{Mat, Scalar, CV_32F},
+            prelude::{MatTraitConst, MatTraitConstManual, MatTraitManual},
+        };
+
+        #[test]
+        fn fft_shift_test() {
+            // 与 numpy.fft.fftshift 一致：输出 (r, c) 处为输入 ((r - rows / 2) mod rows, (c - cols / 2) mod cols) 处的值
+            for (rows, cols) in [(5, 7), (4, 6), (1, 3)] {
+                let mut image =
+                    Mat::new_rows_cols_with_default(rows, cols, CV_32F, Scalar::all(0.0)).unwrap();
+                for row in 0..rows {
+                    for (col, item) in image.at_row_mut::<f32>(row).unwrap().iter_mut().enumerate()
+                    {
+                        *item = (row * cols + col as i32) as f32;
+                    }
+                }
+
+                let shifted = fft::fft_shift(&image).unwrap();
+                assert_eq!((shifted.rows(), shifted.cols()), (rows, cols));
+                // 零频位于 (cols / 2, rows / 2) 处
+                assert_eq!(*shifted.at_2d::<f32>(rows / 2, cols / 2).unwrap(), 0.0);
+                for row in 0..rows {
+                    for col in 0..cols {
+                        let src_row = (row - rows / 2).rem_euclid(rows);
+                        let src_col = (col - cols / 2).rem_euclid(cols);
+                        assert_eq!(
+                            *shifted.at_2d::<f32>(row, col).unwrap(),
+                            *image.at_2d::<f32>(src_row, src_col).unwrap(),
+                            "{}x{} ({}, {})",
+                            rows,
+                            cols,
+                            row,
+                            col
+                        );
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn spectrum_candidate_angles_test() {
+            let angles = fft::get_spectrum_candidate_angles(45, 0.2);
+            assert_eq!(angles.len(), 450);
+            assert_eq!(angles[0], -45.0);
+            assert!(angles.iter().all(|angle| (-45.0..45.0).contains(angle)));
+
+            let angles = fft::get_spectrum_candidate_angles(1, 0.5);
+            assert_eq!(angles, vec![-1.0, -0.5, 0.0, 0.5]);
+        }
+
+        #[test]
+        fn spectrum_angle_energies_test() {
+            // 在中心位于 (32, 32) 的频谱上绘制方向为 3deg + 90deg 的亮线，对应 3deg 的直线
+            let (width, height) = (64usize, 64usize);
+            let mut spectrum = vec![0.0; width * height];
+            let radian = (3.0f64 + 90.0).to_radians();
+            for step in -300..=300 {
+                let radius = step as f64 / 10.0;
+                let x = (32.0 + radius * radian.cos()).round() as usize;
+                let y = (32.0 + radius * radian.sin()).round() as usize;
+                spectrum[y * width + x] = 255.0;
+            }
+
+            let angles: Vec<f64> = (-10..=10).map(|index| index as f64).collect();
+            let energies = fft::get_spectrum_angle_energies(&spectrum, width, height, &angles);
+            let max_index = (0..energies.len())
+                .max_by(|a, b| energies[*a].partial_cmp(&energies[*b]).unwrap())
+                .unwrap();
+            assert_eq!(angles[max_index], 3.0);
+
+            // 频谱过小时不进行积分
+            let energies = fft::get_spectrum_angle_energies(&[0.0; 4], 2, 2, &angles);
+            assert!(energies.iter().all(|energy| *energy == 0.0));
+        }
+    }
+
+    mod detector {
+        use crate::{
+            detector::{
+                EdgesDetector, FftDetector, FftSpectrumDetector, FourierTransformDetector,
+                HoughDetector, PipelineDetector, ProjectionDetector, ProjectionSweepDetector,
+                RadonDetector, SkewDetector,
+            },
+            pipeline::PreprocessPipeline,
+            Error,
+        };
+        use std::collections::HashSet;
+
+        #[test]
+        fn skew_detector_test() {
+            let src = super::get_lined_image(3.0);
+
+            // 直线特征明显，以下检测器的结果应接近真实偏转角
+            let accurate_detectors: Vec<Box<dyn SkewDetector>> = vec![
+                Box::new(ProjectionDetector::default()),
+                Box::new(ProjectionSweepDetector {
+                    resize_scale: 1.0,
+                    ..Default::default()
+                }),
+                Box::new(HoughDetector::default()),
+                Box::new(RadonDetector::default()),
+            ];
+            for detector in accurate_detectors.iter() {
+                let result = detector.detect(&src).unwrap();
+                assert!(
+                    (result.angle - 3.0).abs() < 0.5,
+                    "{}: {}deg",
+                    detector.name(),
+                    result.angle
+                );
+            }
+
+            let other_detectors: Vec<Box<dyn SkewDetector>> = vec![
+                Box::new(EdgesDetector::default()),
+                Box::new(FourierTransformDetector::default()),
+                Box::new(FftSpectrumDetector::default()),
+                Box::new(PipelineDetector::new(
+                    PreprocessPipeline::projection_default(248, 230),
+                    Box::new(ProjectionDetector::default()),
+                )),
+            ];
+            for detector in other_detectors.iter() {
+                assert!(detector.detect(&src).is_ok(), "{}", detector.name());
+            }
+
+            // 基于线段检测的方法在频谱中找不到直线时返回 NoFeatures
+            let fft_detector = FftDetector::default();
+            assert!(matches!(
+                fft_detector.detect(&src),
+                Ok(_) | Err(Error::NoFeatures(_))
+            ));
+
+            // 管道检测器沿用内部检测器的名称，其余检测器名称互不相同
+            assert_eq!(other_detectors[3].name(), "projection");
+            let names: HashSet<&str> = accurate_detectors
+                .iter()
+                .chain(other_detectors[..3].iter())
+                .map(|detector| detector.name())
+                .chain([fft_detector.name()])
+                .collect();
+            assert_eq!(names.len(), 8);
+        }
+    }
+
+    mod ensemble {
+        use crate::{
+            detector::SkewDetector,
+            omr::{
+                ensemble::{self, EnsembleDetector, EnsemblePolicy, ReviewReason},
+                OmrResult, ResultStatus,
+            },
+            transfer::TransformableMatrix,
+            Error, Result,
+        };
+        use opencv::core::{Mat, Scalar, CV_8UC3};
+
+        fn get_result(angle: f64, status: ResultStatus) -> OmrResult {
+            OmrResult {
+                angle,
+                status,
+                candidates: vec![angle],
+                confidence: 1.0,
+                uncertainty: 0.1,
+            }
+        }
+
+        #[test]
+        fn fuse_results_test() {
+            // 多数一致即可
+            let policy = EnsemblePolicy {
+                min_agreement: 0.5,
+                ..EnsemblePolicy::default()
+            };
+            let result = ensemble::fuse_results(
+                vec![
+                    ("projection", get_result(1.0, ResultStatus::Believed), 3.0),
+                    ("hough", get_result(1.05, ResultStatus::Believed), 1.0),
+                    ("fft", get_result(7.0, ResultStatus::Believed), 1.0),
+                    ("radon", get_result(0.0, ResultStatus::NotAResult), 1.0),
+                ],
+                &policy,
+            );
+            // 1.0 的权重超过有效权重的一半，中位数不存在并列
+            assert_eq!(result.angle, 1.0);
+            assert!(!result.need_check);
+            assert_eq!(result.agreement, 0.8);
+            assert_eq!(result.votes[3].weight, 0.0);
+            assert!(!result.votes[2].agreed);
+
+            // 三种方案互不一致，一致结果的权重占比不足
+            let result = ensemble::fuse_results(
+                vec![
+                    ("projection", get_result(1.0, ResultStatus::Believed), 1.0),
+                    ("hough", get_result(3.0, ResultStatus::Believed), 1.0),
+                    ("fft", get_result(6.0, ResultStatus::Believed), 1.0),
+                ],
+                &policy,
+            );
+            assert_eq!(result.angle, 3.0);
+            assert!(result.need_check);
+            assert_eq!(
+                result.reasons,
+                vec![ReviewReason::Disagreement {
+                    agreement: 1.0 / 3.0,
+                    required: 0.5
+                }]
+            );
+
+            let result = ensemble::fuse_results(
+                vec![("hough", get_result(0.0, ResultStatus::NotAResult), 1.0)],
+                &policy,
+            );
+            assert_eq!(result.reasons, vec![ReviewReason::NoResult]);
+            assert_eq!(result.to_omr_result().status, ResultStatus::NotAResult);
+        }
+
+        #[test]
+        fn two_members_disagreement_test() {
+            // 默认策略下，投影与边缘检测两种方案的分歧需要复查
+            let policy = EnsemblePolicy::default();
+            let result = ensemble::fuse_results(
+                vec![
+                    ("projection", get_result(1.0, ResultStatus::NeedCheck), 2.0),
+                    ("edges", get_result(3.0, ResultStatus::Believed), 1.0),
+                ],
+                &policy,
+            );
+            assert_eq!(result.angle, 1.0);
+            assert!(result.need_check);
+            assert_eq!(
+                result.reasons,
+                vec![ReviewReason::Disagreement {
+                    agreement: 2.0 / 3.0,
+                    required: 1.0
+                }]
+            );
+            assert_eq!(result.to_omr_result().status, ResultStatus::NeedCheck);
+
+            // 两种方案一致时，投影方案的 NeedCheck 由边缘检测确认
+            let result = ensemble::fuse_results(
+                vec![
+                    ("projection", get_result(1.0, ResultStatus::NeedCheck), 2.0),
+                    ("edges", get_result(1.05, ResultStatus::Believed), 1.0),
+                ],
+                &policy,
+            );
+            assert!(!result.need_check);
+            assert_eq!(result.agreement, 1.0);
+        }
+
+        /// 返回固定结果或错误的检测器
+        struct FixedDetector(Option<f64>);
+        impl SkewDetector for FixedDetector {
+            fn name(&self) -> &'static str {
+                if self.0.is_some() {
+                    "fixed"
+                } else {
+                    "failing"
+                }
+            }
+
+            fn detect(&self, _: &TransformableMatrix) -> Result<OmrResult> {
+                match self.0 {
+                    Some(angle) => Ok(get_result(angle, ResultStatus::Believed)),
+                    None => Err(Error::NoFeatures(String::from("no lines"))),
+                }
+            }
+        }
+
+        #[test]
+        fn detect_all_test() {
+            let src = TransformableMatrix::from_matrix(
+                &Mat::new_rows_cols_with_default(10, 10, CV_8UC3, Scalar::all(255.0)).unwrap(),
+            );
+
+            // 单个检测器出错时不影响其余检测器的结果
+            let ensemble = EnsembleDetector::new(EnsemblePolicy::default())
+                .member(Box::new(FixedDetector(None)), 2.0)
+                .member(Box::new(FixedDetector(Some(1.0))), 1.0);
+            let result = ensemble.detect_all(&src).unwrap();
+            assert_eq!(result.angle, 1.0);
+            assert!(!result.need_check);
+            assert_eq!(result.votes[0].weight, 0.0);
+            assert_eq!(result.votes[0].result.status, ResultStatus::NotAResult);
+            assert!(result.votes[0].error.is_some());
+            assert!(result.votes[1].error.is_none());
+
+            // 已有可信结果时不运行备用检测器
+            let ensemble = EnsembleDetector::new(EnsemblePolicy::default())
+                .member(Box::new(FixedDetector(Some(1.0))), 2.0)
+                .fallback(Box::new(FixedDetector(Some(3.0))), 1.0);
+            let result = ensemble.detect_all(&src).unwrap();
+            assert_eq!(result.votes.len(), 1);
+            assert!(!result.need_check);
+            let ensemble = EnsembleDetector::new(EnsemblePolicy::default())
+                .member(Box::new(FixedDetector(None)), 2.0)
+                .fallback(Box::new(FixedDetector(Some(3.0))), 1.0);
+            let result = ensemble.detect_all(&src).unwrap();
+            assert_eq!(result.votes.len(), 2);
+            assert_eq!(result.angle, 3.0);
+
+            // 权重为负数、NaN 或无穷大
+            for weight in [-1.0, f64::NAN, f64::INFINITY] {
+                let ensemble = EnsembleDetector::new(EnsemblePolicy::default())
+                    .member(Box::new(FixedDetector(Some(1.0))), 1.0)
+                    .fallback(Box::new(FixedDetector(Some(1.0))), weight);
+                assert!(matches!(
+                    ensemble.detect_all(&src),
+                    Err(Error::InvalidParameter(_))
+                ));
+            }
+
+            let ensemble = EnsembleDetector::new(EnsemblePolicy::default())
+                .member(Box::new(FixedDetector(None)), 1.0);
+            assert_eq!(
+                ensemble.detect_all(&src).unwrap().reasons,
+                vec![ReviewReason::NoResult]
+            );
+            assert!(matches!(
+                ensemble.detect_all(&TransformableMatrix::default()),
+                Err(Error::EmptyImage)
+            ));
+        }
+
+        #[test]
+        fn policy_validate_test() {
+            assert!(EnsemblePolicy::default().validate().is_ok());
+            for policy in [
+                EnsemblePolicy {
+                    min_agreement: 1.5,
+                    ..EnsemblePolicy::default()
+                },
+                EnsemblePolicy {
+                    min_agreement: f64::NAN,
+                    ..EnsemblePolicy::default()
+                },
+                EnsemblePolicy {
+                    agreement_range: -0.1,
+                    ..EnsemblePolicy::default()
+                },
+            ] {
+                assert!(matches!(policy.validate(), Err(Error::InvalidParameter(_))));
+            }
+            assert!(matches!(
+                EnsemblePolicy::from_json_str(r#"{ "min_agreement": 2.0 }"#),
+                Err(Error::InvalidParameter(_))
+            ));
+            assert!(matches!(
+                EnsemblePolicy::from_json_str("{"),
+                Err(Error::Json(_))
+            ));
+        }
+    }
+
+    mod projection {
+        use crate::{
+            detector::ProjectionDetector,
+            omr::{self, ProjectionSearchMode, ResultStatus},
+            projection::{self, sparse::SparseBinaryImage},
+            transfer::{self, TransformableMatrix},
+            types::{PeakInterpolation, RotateClipStrategy},
+            Error,
+        };
+        use opencv::{
+            core::{Mat, Scalar, BORDER_CONSTANT, CV_8UC1},
+            imgcodecs, imgproc,
+            prelude::{MatTraitConst, MatTraitManual},
+        };
+        use rand::{Rng, SeedableRng};
+        use std::{sync::Arc, time::Instant};
+
+        #[test]
+        fn sparse_projection_test() {
+            let mut random = rand::rngs::StdRng::seed_from_u64(0);
+            // 宽、高为奇数时旋转中心不在像素中心
+            let mut mat =
+                Mat::new_rows_cols_with_default(57, 101, CV_8UC1, Scalar::all(255.0)).unwrap();
+            for row_index in 0..mat.rows() {
+                for item in mat.at_row_mut::<u8>(row_index).unwrap().iter_mut() {
+                    if random.gen_range(0..7) == 0 {
+                        *item = 0;
+                    }
+                }
+            }
+            let thresh_image = TransformableMatrix::from_matrix(&mat);
+            let sparse_image = SparseBinaryImage::from_mat(&mat).unwrap();
+
+            // 内置投影流程以缩放比例旋转缩小后的二值图，缩放比例通常小于 1
+            for scale in [1.0, 0.5, 0.37] {
+                for deg in -225..225 {
+                    let angle = deg as f64 * 0.2;
+                    let rotated_image = transfer::rotate_mat(
+                        &thresh_image,
+                        angle,
+                        scale,
+                        imgproc::WARP_POLAR_LINEAR,
+                        BORDER_CONSTANT,
+                        Scalar::new(255.0, 255.0, 255.0, 0.0),
+                        RotateClipStrategy::DEFAULT,
+                    )
+                    .unwrap();
+
+                    assert_eq!(
+                        sparse_image
+                            .get_projection_standard_deviations(angle, scale)
+                            .unwrap(),
+                        transfer::get_projection_standard_deviations(&rotated_image).unwrap(),
+                        "{}deg x{}",
+                        angle,
+                        scale
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn projection_sweep_test() {
+            let mut random = rand::rngs::StdRng::seed_from_u64(1);
+            let mut mat =
+                Mat::new_rows_cols_with_default(60, 80, CV_8UC1, Scalar::all(255.0)).unwrap();
+            for row_index in 0..mat.rows() {
+                for item in mat.at_row_mut::<u8>(row_index).unwrap().iter_mut() {
+                    if random.gen_range(0..5) == 0 {
+                        *item = 0;
+                    }
+                }
+            }
+            let sparse_image = Arc::new(SparseBinaryImage::from_mat(&mat).unwrap());
+            let angles: Vec<f64> = (-50..50).map(|deg| deg as f64 * 0.2).collect();
+
+            // 结果与线程数无关
+            let single_thread = projection::get_projection_standard_deviations_sweep(
+                &sparse_image,
+                &angles,
+                1.0,
+                1,
+            )
+            .unwrap();
+            for threads in [2, 3, 8, 200] {
+                assert_eq!(
+                    projection::get_projection_standard_deviations_sweep(
+                        &sparse_image,
+                        &angles,
+                        1.0,
+                        threads
+                    )
+                    .unwrap(),
+                    single_thread
+                );
+            }
+
+            // 两条曲线的最大值位于不同角度时取平方和最大者，平方和相同时取序号较小者
+            assert_eq!(
+                projection::get_most_possible_index(&[1.0, 3.0, 2.0], &[1.0, 3.0, 2.0]),
+                Some(1)
+            );
+            assert_eq!(
+                projection::get_most_possible_index(&[3.0, 1.0, 1.0], &[1.0, 1.0, 3.0]),
+                Some(0)
+            );
+            assert_eq!(
+                projection::get_most_possible_index(&[3.0, 1.0, 1.0], &[1.0, 1.0, 4.0]),
+                Some(2)
+            );
+            assert_eq!(projection::get_most_possible_index(&[], &[]), None);
+        }
+
+        fn get_result(
+            src: &TransformableMatrix,
+            search_mode: ProjectionSearchMode,
+            threads: usize,
+        ) -> omr::OmrResult {
+            let detector = ProjectionDetector {
+                search_mode,
+                threads,
+                ..ProjectionDetector::default()
+            };
+            omr::get_result_from_projection(src.get_mat(), &detector).unwrap()
+        }
+
+        #[test]
+        fn threads_test() {
+            let src = super::get_lined_image(3.0);
+            let single = get_result(&src, ProjectionSearchMode::Exhaustive, 1);
+
+            // 并行计算的结果与单线程一致，线程数多于角度数时同样适用
+            for threads in [2, 3, 4096] {
+                let result = get_result(&src, ProjectionSearchMode::Exhaustive, threads);
+                assert_eq!(result.angle, single.angle);
+                assert_eq!(result.status, single.status);
+                assert_eq!(result.candidates, single.candidates);
+            }
+        }
+
+        #[test]
+        fn angle_range_overflow_test() {
+            // 最大偏转角度与角度步长之比超出 u16 的范围时返回错误，而不是截断角度范围
+            let src = super::get_lined_image(3.0);
+            let detector = ProjectionDetector {
+                max_angle: 90,
+                angle_step: 0.001,
+                ..ProjectionDetector::default()
+            };
+            assert!(matches!(
+                omr::get_result_from_projection(src.get_mat(), &detector),
+                Err(Error::InvalidParameter(_))
+            ));
+
+            let thresh_image = transfer::transfer_gray_image_to_thresh_binary(
+                &transfer::transfer_rgb_image_to_gray_image(&src).unwrap(),
+            )
+            .unwrap();
+            assert!(matches!(
+                projection::get_result_from_thresh_binary(
+                    &thresh_image,
+                    90,
+                    0.001,
+                    1,
+                    PeakInterpolation::NONE
+                ),
+                Err(Error::InvalidParameter(_))
+            ));
+        }
+
+        #[test]
+        fn coarse_to_fine_test() {
+            let src = super::get_lined_image(3.0);
+            let exhaustive = get_result(&src, ProjectionSearchMode::Exhaustive, 1);
+            let coarse_to_fine = get_result(&src, ProjectionSearchMode::coarse_to_fine(), 1);
+
+            // 由粗到精搜索的结果与遍历搜索相差不超过一个角度步长
+            assert!((exhaustive.angle - 3.0).abs() < 0.5, "{}", exhaustive.angle);
+            assert!(
+                (coarse_to_fine.angle - exhaustive.angle).abs() <= 0.2 + 1e-9,
+                "{} {}",
+                coarse_to_fine.angle,
+                exhaustive.angle
+            );
+            assert_ne!(coarse_to_fine.status, ResultStatus::NotAResult);
+            assert!((0.0..=1.0).contains(&coarse_to_fine.confidence));
+            // 估计误差不超过精细搜索窗口的半宽
+            assert!(coarse_to_fine.uncertainty <= 5.0 * 0.2);
+
+            // 最大偏转角度恰为粗搜索步长的整数倍时，粗搜索范围与遍历搜索一致，不超出最大偏转角度
+            let coarse_to_fine = get_result(
+                &super::get_lined_image(-44.0),
+                ProjectionSearchMode::coarse_to_fine(),
+                1,
+            );
+            assert!(coarse_to_fine.angle >= -45.0 && coarse_to_fine.angle < 45.0);
+        }
+
+        #[allow(dead_code)]
+        // #[test]
+        fn coarse_to_fine_bench_test() {
+            let mut src_images = vec![];
+            for entry in walkdir::WalkDir::new(super::DATA_SET_DIR_PATH) {
+                let this_entry = entry.unwrap();
+                if !this_entry.metadata().unwrap().is_file() {
+                    continue;
+                }
+                src_images.push(
+                    TransformableMatrix::new(
+                        &this_entry.path().display().to_string(),
+                        imgcodecs::IMREAD_COLOR,
+                    )
+                    .unwrap(),
+                );
+            }
+
+            let mut elapsed = [0u128; 2];
+            let mut max_difference = 0.0f64;
+            for src in src_images.iter() {
+                let start = Instant::now();
+                let exhaustive = get_result(src, ProjectionSearchMode::Exhaustive, 1);
+                elapsed[0] += start.elapsed().as_micros();
+
+                let start = Instant::now();
+                let coarse_to_fine = get_result(src, ProjectionSearchMode::coarse_to_fine(), 1);
+                elapsed[1] += start.elapsed().as_micros();
+
+                max_difference =
+                    max_difference.max((exhaustive.angle - coarse_to_fine.angle).abs());
+            }
+
+            println!(
+                ">> {} images, exhaustive {}us, coarse to fine {}us, speed up {:.2}x, max difference {}deg",
+                src_images.len(),
+                elapsed[0],
+                elapsed[1],
+                elapsed[0] as f64 / elapsed[1].max(1) as f64,
+                max_difference
+            );
+        }
+    }
+
+    mod template {
+        use crate::{
+            anchor::{AnchorDetector, Anchors},
+            template::{BubbleField, RelativePoint, SheetTemplate},
+            Error,
+        };
+        use opencv::core::Point2f;
+
+        const TEMPLATE_JSON: &str = r#"{
+            "name": "sample",
+            "bubble_size": { "width": 0.02, "height": 0.01 },
+            "question_blocks": [{
+                "first_question": 1,
+                "question_count": 5,
+                "option_count": 4,
+                "origin": { "x": 0.1, "y": 0.3 },
+                "question_spacing": 0.02,
+                "option_spacing": 0.03,
+                "layout": "horizontal"
+            }],
+            "id_block": {
+                "digit_count": 6,
+                "origin": { "x": 0.6, "y": 0.1 },
+                "digit_spacing": 0.03,
+                "value_spacing": 0.015
+            }
+        }"#;
+
+        #[test]
+        fn template_test() {
+            let template = SheetTemplate::from_json_str(TEMPLATE_JSON).unwrap();
+            let regions = template.project(1000, 2000);
+            assert_eq!(regions.len(), 5 * 4 + 6 * 10);
+
+            // 第 2 题第 3 个选项框中心位于 (0.1 + 2 * 0.03, 0.3 + 0.02) 处
+            let region = regions
+                .iter()
+                .find(|region| {
+                    region.field
+                        == BubbleField::Question {
+                            question: 2,
+                            option: 2,
+                        }
+                })
+                .unwrap();
+            assert_eq!((region.rect.x, region.rect.y), (160 - 10, 640 - 10));
+            assert_eq!((region.rect.width, region.rect.height), (20, 20));
+
+            // 序列化后能够还原
+            let json = template.to_json_string().unwrap();
+            assert_eq!(SheetTemplate::from_json_str(&json).unwrap(), template);
+
+            // 未指定定位块检测器时取默认值，指定时未给出的字段取默认值
+            assert_eq!(template.anchor_detector, AnchorDetector::default());
+            let anchor_json = TEMPLATE_JSON.replace(
+                "\"name\": \"sample\",",
+                "\"name\": \"sample\", \"anchor_detector\": { \"output_margin\": 40 },",
+            );
+            assert_eq!(
+                SheetTemplate::from_json_str(&anchor_json)
+                    .unwrap()
+                    .anchor_detector,
+                AnchorDetector {
+                    output_margin: 40,
+                    ..AnchorDetector::default()
+                }
+            );
+
+            // 超出答题卡范围的模板不合法
+            let invalid_json =
+                TEMPLATE_JSON.replace("\"question_count\": 5", "\"question_count\": 50");
+            assert!(matches!(
+                SheetTemplate::from_json_str(&invalid_json),
+                Err(Error::InvalidConfig(_))
+            ));
+            assert!(matches!(
+                SheetTemplate::from_json_str("{ \"name\": "),
+                Err(Error::Json(_))
+            ));
+            assert!(matches!(
+                SheetTemplate::load("./not_exists.json"),
+                Err(Error::Io(_))
+            ));
+            // 题号溢出
+            let overflow_json =
+                TEMPLATE_JSON.replace("\"first_question\": 1", "\"first_question\": 4294967295");
+            assert!(matches!(
+                SheetTemplate::from_json_str(&overflow_json),
+                Err(Error::InvalidConfig(_))
+            ));
+            // 最后一题的题号恰为 u32::MAX 时不溢出
+            let last_json = overflow_json.replace("\"question_count\": 5", "\"question_count\": 1");
+            assert!(SheetTemplate::from_json_str(&last_json).is_ok());
+        }
+
+        #[test]
+        fn project_with_anchors_test() {
+            let mut template = SheetTemplate::from_json_str(TEMPLATE_JSON).unwrap();
+            let anchors = Anchors {
+                top_left: Point2f::new(100.0, 200.0),
+                top_right: Point2f::new(900.0, 200.0),
+                bottom_right: Point2f::new(900.0, 1800.0),
+                bottom_left: Point2f::new(100.0, 1800.0),
+            };
+            // 模板未定义定位块
+            assert!(matches!(
+                template.project_with_anchors(&anchors),
+                Err(Error::InvalidConfig(_))
+            ));
+
+            // 定位块位置与图像尺寸成比例时，结果与按图像尺寸投影一致
+            template.anchors = [(0.1, 0.1), (0.9, 0.1), (0.9, 0.9), (0.1, 0.9)]
+                .into_iter()
+                .map(|(x, y)| RelativePoint { x, y })
+                .collect();
+            template.validate().unwrap();
+            let regions = template.project_with_anchors(&anchors).unwrap();
+            let expected_regions = template.project(1000, 2000);
+            assert_eq!(regions.len(), expected_regions.len());
+            for (region, expected) in regions.iter().zip(expected_regions.iter()) {
+                assert_eq!(region.field, expected.field);
+                assert!((region.rect.x - expected.rect.x).abs() <= 1);
+                assert!((region.rect.y - expected.rect.y).abs() <= 1);
+                assert!((region.rect.width - expected.rect.width).abs() <= 1);
+                assert!((region.rect.height - expected.rect.height).abs() <= 1);
+            }
+
+            // 图像中的定位块整体平移时，选项框随之平移
+            let shifted_anchors = Anchors {
+                top_left: Point2f::new(150.0, 260.0),
+                top_right: Point2f::new(950.0, 260.0),
+                bottom_right: Point2f::new(950.0, 1860.0),
+                bottom_left: Point2f::new(150.0, 1860.0),
+            };
+            let shifted_regions = template.project_with_anchors(&shifted_anchors).unwrap();
+            for (region, shifted) in regions.iter().zip(shifted_regions.iter()) {
+                assert!((shifted.rect.x - region.rect.x - 50).abs() <= 1);
+                assert!((shifted.rect.y - region.rect.y - 60).abs() <= 1);
+            }
+        }
+    }
+
+    mod orientation {
+        use crate::{
+            orientation::{self, Orientation, OrientationDetector},
+            transfer::TransformableMatrix,
+        };
+        use opencv::{
+            core::{self, Mat, Point, Rect, Scalar, CV_8UC3},
+            imgproc,
+        };
+
+        #[test]
+        fn get_orientation_test() {
+            // 300 x 400 的纵向答题卡，上侧为墨迹较重的表头，其余为题目行
+            let mut sheet =
+                Mat::new_rows_cols_with_default(400, 300, CV_8UC3, Scalar::all(255.0)).unwrap();
+            imgproc::rectangle(
+                &mut sheet,
+                Rect::new(20, 10, 260, 40),
+                Scalar::all(0.0),
+                imgproc::FILLED,
+                imgproc::LINE_8,
+                0,
+            )
+            .unwrap();
+            for y in (100..400).step_by(20) {
+                imgproc::line(
+                    &mut sheet,
+                    Point::new(20, y),
+                    Point::new(280, y),
+                    Scalar::all(0.0),
+                    2,
+                    imgproc::LINE_8,
+                    0,
+                )
+                .unwrap();
+            }
+
+            // 顺时针旋转后，摆正所需的逆时针旋转角度与之相同
+            let detector = OrientationDetector::default();
+            for (rotate_code, expected) in [
+                (None, Orientation::Deg0),
+                (Some(core::ROTATE_90_CLOCKWISE), Orientation::Deg90),
+                (Some(core::ROTATE_180), Orientation::Deg180),
+                (Some(core::ROTATE_90_COUNTERCLOCKWISE), Orientation::Deg270),
+            ] {
+                let rotated = match rotate_code {
+                    Some(rotate_code) => {
+                        let mut dst = Mat::default();
+                        core::rotate(&sheet, &mut dst, rotate_code).unwrap();
+                        dst
+                    }
+                    None => sheet.clone(),
+                };
+                let result = orientation::get_orientation(
+                    &TransformableMatrix::from_matrix(&rotated),
+                    &detector,
+                )
+                .unwrap();
+                assert_eq!(result.orientation, expected);
+                assert!(result.confidence > 0.0 && result.confidence <= 1.0);
+            }
+
+            // 空白页面无法判断朝向
+            let blank =
+                Mat::new_rows_cols_with_default(400, 300, CV_8UC3, Scalar::all(255.0)).unwrap();
+            let result =
+                orientation::get_orientation(&TransformableMatrix::from_matrix(&blank), &detector)
+                    .unwrap();
+            assert_eq!(result.orientation, Orientation::Deg0);
+            assert_eq!(result.confidence, 0.0);
+        }
+    }
+
+    mod pipeline {
+        use crate::{
+            detector::ProjectionDetector,
+            omr,
+            pipeline::{PreprocessPipeline, PreprocessStage},
+            transfer::{self, TransformableMatrix},
+            types::{BinarizationStrategy, IlluminationStrategy, RotateClipStrategy},
+        };
+        use opencv::{
+            core::{Mat, Point, Scalar, BORDER_CONSTANT, CV_8UC3},
+            imgproc,
+        };
+
+        #[test]
+        fn pipeline_serde_test() {
+            let pipeline = PreprocessPipeline::new()
+                .gray()
+                .illumination(IlluminationStrategy::MEDIAN { kernel_size: 31 })
+                .erode(3, 3)
+                .shrink_to(248, 230)
+                .threshold(BinarizationStrategy::SAUVOLA {
+                    window_size: 15,
+                    k: 0.34,
+                    r: 128.0,
+                });
+
+            let json = pipeline.to_json_string().unwrap();
+            assert_eq!(PreprocessPipeline::from_json_str(&json).unwrap(), pipeline);
+
+            let pipeline = PreprocessPipeline::from_json_str(
+                r#"{ "stages": [{ "stage": "gray" }, { "stage": "threshold", "strategy": "OTSU" }] }"#,
+            )
+            .unwrap();
+            assert_eq!(
+                pipeline.stages,
+                vec![
+                    PreprocessStage::Gray,
+                    PreprocessStage::Threshold {
+                        strategy: BinarizationStrategy::OTSU
+                    }
+                ]
+            );
+        }
+
+        #[test]
+        fn projection_default_test() {
+            // 尺寸恰为最大尺寸的图像，两种预处理的缩放比例均为 1
+            let mut lined_mat =
+                Mat::new_rows_cols_with_default(230, 248, CV_8UC3, Scalar::all(255.0)).unwrap();
+            for y in (40..200).step_by(20) {
+                imgproc::line(
+                    &mut lined_mat,
+                    Point::new(30, y),
+                    Point::new(218, y),
+                    Scalar::all(0.0),
+                    3,
+                    imgproc::LINE_8,
+                    0,
+                )
+                .unwrap();
+            }
+            let src = transfer::rotate_mat(
+                &TransformableMatrix::from_matrix(&lined_mat),
+                -3.0,
+                1.0,
+                imgproc::INTER_LINEAR,
+                BORDER_CONSTANT,
+                Scalar::new(255.0, 255.0, 255.0, 0.0),
+                RotateClipStrategy::DEFAULT,
+            )
+            .unwrap();
+
+            let detector = ProjectionDetector::default();
+            let builtin_result = omr::get_result_from_projection(src.get_mat(), &detector).unwrap();
+            let pipeline_result = omr::get_result_from_projection_with_pipeline(
+                &src,
+                &PreprocessPipeline::projection_default(248, 230),
+                &detector,
+            )
+            .unwrap();
+
+            assert!(
+                (builtin_result.angle - 3.0).abs() < 0.5,
+                "{}",
+                builtin_result.angle
+            );
+            assert_eq!(pipeline_result.angle, builtin_result.angle);
+            assert_eq!(pipeline_result.status, builtin_result.status);
+        }
+    }
+
+    mod marks {
+        use crate::{
+            omr::marks::{self, MarkReader, MarkState},
+            transfer::TransformableMatrix,
+            Error,
+        };
+        use opencv::{
+            core::{Mat, Point, Rect, Scalar, CV_8UC3},
+            imgproc,
+        };
+
+        #[test]
+        fn read_marks_test() {
+            // 一行四个直径 25 的圆形选项框，依次为已填涂、未填涂、部分填涂、未填涂
+            let mut sheet =
+                Mat::new_rows_cols_with_default(100, 300, CV_8UC3, Scalar::all(255.0)).unwrap();
+            for (index, x) in [50, 100, 150, 200].into_iter().enumerate() {
+                let thickness = if index == 0 { imgproc::FILLED } else { 2 };
+                imgproc::circle(
+                    &mut sheet,
+                    Point::new(x, 50),
+                    12,
+                    Scalar::all(0.0),
+                    thickness,
+                    imgproc::LINE_8,
+                    0,
+                )
+                .unwrap();
+            }
+            // 横穿选项框中部的填涂，约占内部区域的 1/3
+            imgproc::rectangle(
+                &mut sheet,
+                Rect::new(150 - 12, 50 - 2, 25, 5),
+                Scalar::all(0.0),
+                imgproc::FILLED,
+                imgproc::LINE_8,
+                0,
+            )
+            .unwrap();
+            // 尺寸与长宽比均符合选项框要求的斜线，轮廓面积比过小
+            imgproc::line(
+                &mut sheet,
+                Point::new(240, 30),
+                Point::new(270, 70),
+                Scalar::all(0.0),
+                2,
+                imgproc::LINE_8,
+                0,
+            )
+            .unwrap();
+            let src = TransformableMatrix::from_matrix(&sheet);
+
+            let reader = MarkReader::default();
+            let marks = marks::read_marks(&src, &reader).unwrap();
+            assert_eq!(
+                marks.iter().map(|mark| mark.state).collect::<Vec<_>>(),
+                vec![
+                    MarkState::Filled,
+                    MarkState::Empty,
+                    MarkState::Ambiguous,
+                    MarkState::Empty
+                ]
+            );
+            assert!(marks.iter().all(|mark| mark.row == 0));
+            assert_eq!(
+                marks.iter().map(|mark| mark.column).collect::<Vec<_>>(),
+                vec![0, 1, 2, 3]
+            );
+
+            // 向内收缩比例不小于 0.5 时内部区域为空
+            for inner_margin_ratio in [0.5, -0.1, f64::NAN] {
+                let reader = MarkReader {
+                    inner_margin_ratio,
+                    ..MarkReader::default()
+                };
+                assert!(matches!(
+                    marks::read_marks(&src, &reader),
+                    Err(Error::InvalidParameter(_))
+                ));
+            }
+            let reader = MarkReader {
+                empty_threshold: 0.6,
+                ..MarkReader::default()
+            };
+            assert!(matches!(reader.validate(), Err(Error::InvalidParameter(_))));
+        }
+    }
+
+    mod score {
+        use crate::{
+            omr::marks::{FieldMark, MarkState},
+            score::{self, AnswerKey, ScoreReport},
+            template::BubbleField,
+            Error,
+        };
+        use opencv::core::Rect;
+
+        #[test]
+        fn score_sheet_test() {
+            let key = AnswerKey::from_json_str(
+                r#"{
+                    "questions": [
+                        { "question": 1, "correct_options": [0] },
+                        { "question": 2, "correct_options": [1, 3], "weight": 2.0, "partial_credit": "proportional" },
+                        { "question": 3, "correct_options": [2], "weight": 3.0 }
+                    ]
+                }"#,
+            )
+            .unwrap();
+
+            let mark = |field: BubbleField, state: MarkState| FieldMark {
+                field,
+                rect: Rect::new(0, 0, 1, 1),
+                fill_ratio: 0.0,
+                state,
+            };
+            let question = |question: u32, option: u32| BubbleField::Question { question, option };
+            let marks = vec![
+                mark(question(1, 0), MarkState::Filled),
+                mark(question(1, 1), MarkState::Empty),
+                mark(question(2, 1), MarkState::Filled),
+                mark(question(2, 3), MarkState::Empty),
+                mark(question(3, 2), MarkState::Ambiguous),
+                mark(
+                    BubbleField::IdDigit { digit: 0, value: 4 },
+                    MarkState::Filled,
+                ),
+                mark(
+                    BubbleField::IdDigit { digit: 1, value: 2 },
+                    MarkState::Filled,
+                ),
+            ];
+
+            let report = score::score_sheet(&marks, &key);
+            assert_eq!(report.student_id.as_deref(), Some("42"));
+            assert_eq!(report.score, 1.0 + 1.0);
+            assert_eq!(report.max_score, 6.0);
+            assert!(report.need_check);
+            assert_eq!(report.questions[1].selected_options, vec![1]);
+
+            let get_csv = |report: &ScoreReport| {
+                let mut csv = vec![];
+                score::write_csv(&mut csv, &key, &[(String::from("a,b.jpg"), report.clone())])
+                    .map(|_| String::from_utf8(csv).unwrap())
+            };
+            let csv = get_csv(&report).unwrap();
+            let lines: Vec<&str> = csv.lines().collect();
+            assert_eq!(
+                lines[0],
+                "sheet,student_id,score,max_score,need_check,q1,q1_score,q2,q2_score,q3,q3_score"
+            );
+            assert_eq!(lines[1], "\"a,b.jpg\",42,2,6,true,A,1,B,1,,0");
+
+            // 各题按题号与答案对应，与成绩中的顺序无关
+            let mut reversed_report = report.clone();
+            reversed_report.questions.reverse();
+            assert_eq!(get_csv(&reversed_report).unwrap(), csv);
+            let mut missing_report = report.clone();
+            missing_report.questions.pop();
+            assert!(matches!(
+                get_csv(&missing_report),
+                Err(Error::InvalidParameter(_))
+            ));
+
+            // 考号数值超出 0 ~ 9 时需要复查
+            let report = score::score_sheet(
+                &[
+                    mark(question(1, 0), MarkState::Filled),
+                    mark(question(2, 1), MarkState::Filled),
+                    mark(question(2, 3), MarkState::Filled),
+                    mark(question(3, 2), MarkState::Filled),
+                    mark(
+                        BubbleField::IdDigit {
+                            digit: 0,
+                            value: 12,
+                        },
+                        MarkState::Filled,
+                    ),
+                ],
+                &key,
+            );
+            assert_eq!(report.score, report.max_score);
+            assert_eq!(report.student_id.as_deref(), Some("?"));
+            assert!(report.need_check);
+        }
+
+        #[test]
+        fn answer_key_validate_test() {
+            // 没有正确选项的题目
+            assert!(matches!(
+                AnswerKey::from_json_str(
+                    r#"{ "questions": [{ "question": 1, "correct_options": [] }] }"#
+                ),
+                Err(Error::InvalidConfig(_))
+            ));
+            // 重复的题号
+            assert!(matches!(
+                AnswerKey::from_json_str(
+                    r#"{
+                        "questions": [
+                            { "question": 1, "correct_options": [0] },
+                            { "question": 1, "correct_options": [1] }
+                        ]
+                    }"#
+                ),
+                Err(Error::InvalidConfig(_))
+            ));
+            // 分值为负数
+            assert!(matches!(
+                AnswerKey::from_json_str(
+                    r#"{ "questions": [{ "question": 1, "correct_options": [0], "weight": -1.0 }] }"#
+                ),
+                Err(Error::InvalidConfig(_))
+            ));
+            assert!(matches!(
+                AnswerKey::load("./not_exists.json"),
+                Err(Error::Io(_))
+            ));
+        }
+    }
+
+    mod paper {
+        use crate::{transfer::TransformableMatrix, types::PaperCropStrategy};
+        use opencv::{
+            core::{self, Mat, Point, Scalar, CV_8UC3},
+            imgproc,
+            prelude::MatTraitConst,
+            types::{VectorOfPoint, VectorOfVectorOfPoint},
+        };
+
+        /// 在 400 x 300 的黑色背景上绘制白色纸张，`corners` 为纸张的四个顶点
+        fn get_paper_image(corners: &[(i32, i32)]) -> TransformableMatrix {
+            let mut mat =
+                Mat::new_rows_cols_with_default(300, 400, CV_8UC3, Scalar::all(0.0)).unwrap();
+            let polygon = VectorOfPoint::from(
+                corners
+                    .iter()
+                    .map(|(x, y)| Point::new(*x, *y))
+                    .collect::<Vec<Point>>(),
+            );
+            imgproc::fill_poly(
+                &mut mat,
+                &VectorOfVectorOfPoint::from(vec![polygon]),
+                Scalar::all(255.0),
+                imgproc::LINE_8,
+                0,
+                Point::new(0, 0),
+            )
+            .unwrap();
+
+            TransformableMatrix::from_matrix(&mat)
+        }
+
+        fn get_mean(src: &TransformableMatrix) -> f64 {
+            core::mean(src.get_mat(), &core::no_array()).unwrap()[0]
+        }
+
+        #[test]
+        fn crop_to_paper_test() {
+            // 与坐标轴对齐的纸张，裁剪后只剩纸张区域
+            let src = get_paper_image(&[(40, 30), (359, 30), (359, 269), (40, 269)]);
+            let mut cropped = src.clone();
+            assert!(cropped.crop_to_paper(PaperCropStrategy::CROP).unwrap());
+            let size = cropped.get_mat().size().unwrap();
+            assert!((size.width - 320).abs() <= 2 && (size.height - 240).abs() <= 2);
+            assert!(get_mean(&cropped) > 250.0);
+
+            // 倾斜的纸张，外接矩形中仍有背景，拉直后背景基本消除
+            let src = get_paper_image(&[(60, 40), (350, 30), (370, 270), (40, 260)]);
+            let mut cropped = src.clone();
+            assert!(cropped.crop_to_paper(PaperCropStrategy::CROP).unwrap());
+            assert!(get_mean(&cropped) < 240.0);
+            let mut straightened = src.clone();
+            assert!(straightened
+                .crop_to_paper(PaperCropStrategy::STRAIGHTEN)
+                .unwrap());
+            assert!(get_mean(&straightened) > 240.0);
+
+            // 不裁剪，或纸张占满图像时不做处理
+            let mut unchanged = src.clone();
+            assert!(!unchanged.crop_to_paper(PaperCropStrategy::NONE).unwrap());
+            assert_eq!(
+                unchanged.get_mat().size().unwrap(),
+                src.get_mat().size().unwrap()
+            );
+            let mut blank = get_paper_image(&[(0, 0), (399, 0), (399, 299), (0, 299)]);
+            assert!(!blank.crop_to_paper(PaperCropStrategy::CROP).unwrap());
+            assert_eq!(blank.get_mat().size().unwrap().width, 400);
+
+            // 整幅图像中没有足够大的纸张
+            let mut small = get_paper_image(&[(10, 10), (60, 10), (60, 60), (10, 60)]);
+            assert!(!small.crop_to_paper(PaperCropStrategy::CROP).unwrap());
+        }
+    }
+
+    mod binarization {
+        use crate::{transfer, types::BinarizationStrategy, Error};
+        use opencv::{
+            core::{Mat, Scalar, CV_8UC1},
+            prelude::{MatTraitConst, MatTraitConstManual, MatTraitManual},
+        };
+
+        /// 每隔 20 行有一条 3 行高的墨迹
+        pub(super) fn is_ink_row(row_index: i32) -> bool {
+            (20..190).contains(&row_index) && row_index % 20 < 3
+        }
+
+        /// 生成 300 x 200 的光照不均灰度图，背景亮度从左至右由 60 渐变至 250，
+        /// 墨迹亮度为所在位置背景亮度的 0.4 倍
+        pub(super) fn get_uneven_lighting_image() -> Mat {
+            let mut mat =
+                Mat::new_rows_cols_with_default(200, 300, CV_8UC1, Scalar::all(0.0)).unwrap();
+            for row_index in 0..mat.rows() {
+                let is_ink = is_ink_row(row_index);
+                let row = mat.at_row_mut::<u8>(row_index).unwrap();
+                for (col_index, pixel) in row.iter_mut().enumerate() {
+                    let background = 60.0 + 190.0 * col_index as f64 / 299.0;
+                    *pixel = if is_ink {
+                        (background * 0.4).round() as u8
+                    } else {
+                        background.round() as u8
+                    };
+                }
+            }
+
+            mat
+        }
+
+        /// 返回背景被判为白色、墨迹被判为黑色的比例中较小的一个
+        fn get_min_accuracy(strategy: BinarizationStrategy) -> f64 {
+            let dst =
+                transfer::get_thresh_binary_mat(&get_uneven_lighting_image(), strategy).unwrap();
+
+            let (mut background_hit, mut background_count) = (0, 0);
+            let (mut ink_hit, mut ink_count) = (0, 0);
+            for row_index in 0..dst.rows() {
+                let row = dst.at_row::<u8>(row_index).unwrap();
+                if is_ink_row(row_index) {
+                    ink_count += row.len();
+                    ink_hit += row.iter().filter(|pixel| **pixel == 0).count();
+                } else {
+                    background_count += row.len();
+                    background_hit += row.iter().filter(|pixel| **pixel == 255).count();
+                }
+            }
+
+            f64::min(
+                background_hit as f64 / background_count as f64,
+                ink_hit as f64 / ink_count as f64,
+            )
+        }
+
+        #[test]
+        fn uneven_lighting_test() {
+            // 暗处背景比亮处墨迹更暗，任何全局阈值都无法同时分开两者
+            for strategy in [
+                BinarizationStrategy::FIXED { threshold: 127.0 },
+                BinarizationStrategy::OTSU,
+            ] {
+                let accuracy = get_min_accuracy(strategy);
+                assert!(accuracy < 0.9, "{:?}: {}", strategy, accuracy);
+            }
+
+            // 局部阈值随光照变化，可以分开墨迹与背景
+            for strategy in [
+                BinarizationStrategy::MEAN {
+                    block_size: 15,
+                    c: 10.0,
+                },
+                BinarizationStrategy::GAUSSIAN {
+                    block_size: 15,
+                    c: 10.0,
+                },
+                BinarizationStrategy::SAUVOLA {
+                    window_size: 15,
+                    k: 0.2,
+                    r: 128.0,
+                },
+                BinarizationStrategy::NIBLACK {
+                    window_size: 15,
+                    k: -0.5,
+                },
+            ] {
+                let accuracy = get_min_accuracy(strategy);
+                assert!(accuracy > 0.9, "{:?}: {}", strategy, accuracy);
+            }
+        }
+
+        #[test]
+        fn invalid_parameter_test() {
+            let src = get_uneven_lighting_image();
+            for strategy in [
+                BinarizationStrategy::FIXED {
+                    threshold: f64::NAN,
+                },
+                BinarizationStrategy::MEAN {
+                    block_size: 2,
+                    c: 10.0,
+                },
+                BinarizationStrategy::GAUSSIAN {
+                    block_size: 15,
+                    c: f64::INFINITY,
+                },
+                BinarizationStrategy::SAUVOLA {
+                    window_size: 15,
+                    k: 0.2,
+                    r: 0.0,
+                },
+                BinarizationStrategy::SAUVOLA {
+                    window_size: 16,
+                    k: 0.2,
+                    r: 128.0,
+                },
+                BinarizationStrategy::NIBLACK {
+                    window_size: 1,
+                    k: -0.2,
+                },
+                BinarizationStrategy::NIBLACK {
+                    window_size: 15,
+                    k: f64::NAN,
+                },
+            ] {
+                assert!(
+                    matches!(
+                        transfer::get_thresh_binary_mat(&src, strategy),
+                        Err(Error::InvalidParameter(_))
+                    ),
+                    "{:?}",
+                    strategy
+                );
+            }
+        }
+    }
+
+    mod illumination {
+        use super::binarization::{get_uneven_lighting_image, is_ink_row};
+        use crate::{transfer::TransformableMatrix, types::IlluminationStrategy};
+        use opencv::prelude::{MatTraitConst, MatTraitConstManual};
+
+        /// 返回背景像素的最小值、最大值与墨迹像素的最大值
+        fn get_pixel_range(src: &TransformableMatrix) -> (u8, u8, u8) {
+            let mat = src.get_mat();
+            let (mut background_min, mut background_max, mut ink_max) = (u8::MAX, 0, 0);
+            for row_index in 0..mat.rows() {
+                let row = mat.at_row::<u8>(row_index).unwrap();
+                for pixel in row.iter() {
+                    if is_ink_row(row_index) {
+                        ink_max = ink_max.max(*pixel);
+                    } else {
+                        background_min = background_min.min(*pixel);
+                        background_max = background_max.max(*pixel);
+                    }
+                }
+            }
+
+            (background_min, background_max, ink_max)
+        }
+
+        #[test]
+        fn normalize_illumination_test() {
+            let src = TransformableMatrix::from_matrix(&get_uneven_lighting_image());
+            let (background_min, background_max, ink_max) = get_pixel_range(&src);
+            assert!(background_max - background_min > 150);
+            // 亮处的墨迹比暗处的背景更亮
+            assert!(ink_max > background_min);
+
+            let mut unchanged = src.clone();
+            unchanged
+                .normalize_illumination(IlluminationStrategy::NONE)
+                .unwrap();
+            assert_eq!(get_pixel_range(&unchanged), get_pixel_range(&src));
+
+            // 归一化后背景被压平为接近白色，墨迹仍明显暗于背景
+            for strategy in [
+                IlluminationStrategy::MORPHOLOGY { kernel_size: 31 },
+                IlluminationStrategy::MEDIAN { kernel_size: 31 },
+            ] {
+                let mut normalized = src.clone();
+                normalized.normalize_illumination(strategy).unwrap();
+                let (background_min, _, ink_max) = get_pixel_range(&normalized);
+                assert!(background_min >= 240, "{:?}: {}", strategy, background_min);
+                assert!(ink_max < 150, "{:?}: {}", strategy, ink_max);
+            }
+        }
+    }
+
+    mod denoise {
+        use crate::{
+            detector::{ProjectionDetector, SkewDetector},
+            transfer::TransformableMatrix,
+            types::DenoiseStrategy,
+        };
+        use opencv::prelude::{MatTraitConst, MatTraitConstManual, MatTraitManual};
+        use rand::{Rng, SeedableRng};
+
+        /// 以 `probability` 的概率将像素置为纯黑或纯白
+        fn add_salt_and_pepper_noise(
+            src: &TransformableMatrix,
+            probability: f64,
+        ) -> TransformableMatrix {
+            let mut random = rand::rngs::StdRng::seed_from_u64(0);
+            let mut mat = src.get_mat().clone();
+            let channels = mat.channels() as usize;
+            for row_index in 0..mat.rows() {
+                for pixel in mat
+                    .at_row_mut::<u8>(row_index)
+                    .unwrap()
+                    .chunks_mut(channels)
+                {
+                    if random.gen_bool(probability) {
+                        let value = if random.gen_bool(0.5) { 0 } else { 255 };
+                        pixel.fill(value);
+                    }
+                }
+            }
+
+            TransformableMatrix::from_matrix(&mat)
+        }
+
+        /// 统计与原图黑白相反的像素数
+        fn get_flipped_pixel_count(
+            src: &TransformableMatrix,
+            original: &TransformableMatrix,
+        ) -> usize {
+            let (src, original) = (src.get_mat(), original.get_mat());
+            let channels = src.channels() as usize;
+            (0..src.rows())
+                .map(|row_index| {
+                    let src_row = src.at_row::<u8>(row_index).unwrap();
+                    let original_row = original.at_row::<u8>(row_index).unwrap();
+                    src_row
+                        .chunks(channels)
+                        .zip(original_row.chunks(channels))
+                        .filter(|(pixel, original_pixel)| {
+                            (pixel[0] > 127) != (original_pixel[0] > 127)
+                        })
+                        .count()
+                })
+                .sum()
+        }
+
+        #[test]
+        fn median_denoise_test() {
+            let angle = 3.0;
+            let original = super::get_lined_image(angle);
+            let noisy = add_salt_and_pepper_noise(&original, 0.05);
+            let noisy_count = get_flipped_pixel_count(&noisy, &original);
+            assert!(noisy_count > 1000);
+
+            let mut unchanged = noisy.clone();
+            unchanged.denoise(DenoiseStrategy::NONE).unwrap();
+            assert_eq!(get_flipped_pixel_count(&unchanged, &original), noisy_count);
+
+            // 中值滤波去除孤立的噪点，保留 3 像素宽的直线
+            let mut denoised = noisy.clone();
+            denoised
+                .denoise(DenoiseStrategy::MEDIAN { kernel_size: 3 })
+                .unwrap();
+            let denoised_count = get_flipped_pixel_count(&denoised, &original);
+            assert!(
+                denoised_count * 10 < noisy_count,
+                "{} -> {}",
+                noisy_count,
+                denoised_count
+            );
+
+            let result = ProjectionDetector::default().detect(&denoised).unwrap();
+            assert!((result.angle - angle).abs() < 0.5, "{}", result.angle);
+        }
+    }
+
+    mod options {
+        use crate::{
+            omr::{ensemble::EnsemblePolicy, CorrectionOptions, ProjectionSearchMode},
+            orientation::OrientationDetector,
+            types::{IlluminationStrategy, PaperCropStrategy, PeakInterpolation},
+            Error,
+        };
+
+        #[test]
+        fn correction_options_test() {
+            let options = CorrectionOptions::default();
+            assert!(options.validate().is_ok());
+
+            // 角度步长为 0 或非正数
+            for step in [0.0, -0.2, f64::NAN] {
+                assert!(matches!(
+                    options.clone().projection_angle_step(step).validate(),
+                    Err(Error::InvalidParameter(_))
+                ));
+            }
+            // 最大偏转角度与角度步长之比超出 u16 的范围
+            assert!(matches!(
+                options
+                    .clone()
+                    .projection_max_angle(90)
+                    .projection_angle_step(0.001)
+                    .validate(),
+                Err(Error::InvalidParameter(_))
+            ));
+            // 检测图像的最大尺寸不大于 0 时不限制该方向的尺寸
+            assert!(options
+                .clone()
+                .projection_max_size(0, 230)
+                .validate()
+                .is_ok());
+            assert!(options
+                .clone()
+                .projection_max_size(-1, -1)
+                .validate()
+                .is_ok());
+            assert!(matches!(
+                options.clone().hough(-1.0, 50.0).validate(),
+                Err(Error::InvalidParameter(_))
+            ));
+            assert!(matches!(
+                options
+                    .clone()
+                    .ensemble_policy(EnsemblePolicy {
+                        min_agreement: 1.5,
+                        ..EnsemblePolicy::default()
+                    })
+                    .validate(),
+                Err(Error::InvalidParameter(_))
+            ));
+            assert!(matches!(
+                options
+                    .clone()
+                    .projection_search_mode(ProjectionSearchMode::CoarseToFine {
+                        coarse_scale: 0.0,
+                        coarse_step_multiple: 5,
+                        peaks: 3,
+                    })
+                    .validate(),
+                Err(Error::InvalidParameter(_))
+            ));
+            assert!(matches!(
+                options
+                    .clone()
+                    .orientation_detector(OrientationDetector {
+                        band_ratio: 0.6,
+                        ..OrientationDetector::default()
+                    })
+                    .validate(),
+                Err(Error::InvalidParameter(_))
+            ));
+
+            // 未指定的字段取默认值
+            let options = CorrectionOptions::from_json_str(
+                r#"{ "projection_angle_step": 0.1, "projection_peak_interpolation": "PARABOLIC", "paper_crop_strategy": "CROP" }"#,
+            )
+            .unwrap();
+            assert_eq!(
+                options,
+                CorrectionOptions::default()
+                    .projection_angle_step(0.1)
+                    .projection_peak_interpolation(PeakInterpolation::PARABOLIC)
+                    .paper_crop_strategy(PaperCropStrategy::CROP)
+            );
+            // 解析出的参数同样需要校验
+            assert!(matches!(
+                CorrectionOptions::from_json_str(r#"{ "projection_angle_step": 0 }"#),
+                Err(Error::InvalidParameter(_))
+            ));
+
+            let options = CorrectionOptions::default()
+                .projection_search_mode(ProjectionSearchMode::coarse_to_fine())
+                .illumination_strategy(IlluminationStrategy::MEDIAN { kernel_size: 31 })
+                .orientation_detector(OrientationDetector::default());
+            let json = serde_json::to_string(&options).unwrap();
+            assert_eq!(
+                serde_json::from_str::<CorrectionOptions>(&json).unwrap(),
+                options
+            );
+        }
+    }
+
+    mod correction {
+        use crate::{
+            omr::{self, CorrectionOptions},
+            transfer::{self, TransformableMatrix},
+            types::ImageFormat,
+            Error,
+        };
+        use opencv::{imgcodecs, prelude::MatTraitConst};
+
+        #[test]
+        fn in_memory_correction_test() {
+            // 含有若干水平线的图像，旋转后作为输入
+            let src = super::get_lined_image(3.0);
+
+            let options = CorrectionOptions::default().projection_max_size(320, 240);
+            let buffer = src.encode(ImageFormat::PNG, 3).unwrap();
+            let from_bytes = omr::correct_bytes(&buffer, &options).unwrap();
+            let from_matrix = omr::correct_matrix(&src, &options).unwrap();
+
+            // PNG 为无损编码，两种输入的结果一致
+            assert_eq!(from_bytes.result.angle, from_matrix.result.angle);
+            assert_eq!(from_bytes.result.need_check, from_matrix.result.need_check);
+            assert!(
+                (from_matrix.result.angle - 3.0).abs() < 0.5,
+                "{}",
+                from_matrix.result.angle
+            );
+            for corrected in [&from_bytes, &from_matrix] {
+                let mat = corrected.image.get_mat();
+                assert!(mat.rows() >= src.get_mat().rows() && mat.cols() >= src.get_mat().cols());
+            }
+
+            let output = from_bytes.image.encode(ImageFormat::JPEG, 100).unwrap();
+            let decoded =
+                TransformableMatrix::from_bytes(&output, imgcodecs::IMREAD_COLOR).unwrap();
+            assert_eq!(
+                decoded.get_mat().size().unwrap(),
+                from_bytes.image.get_mat().size().unwrap()
+            );
+
+            assert!(matches!(
+                omr::correct_bytes(b"not an image", &options),
+                Err(Error::UnreadableInput(_))
+            ));
+            assert!(matches!(
+                omr::correct_matrix(&TransformableMatrix::default(), &options),
+                Err(Error::EmptyImage)
+            ));
+            assert!(matches!(
+                omr::correct_matrix(&src, &options.clone().projection_angle_step(0.0)),
+                Err(Error::InvalidParameter(_))
+            ));
+            // 灰度图像不是合法的输入
+            let gray_src = transfer::transfer_rgb_image_to_gray_image(&src).unwrap();
+            assert!(matches!(
+                omr::correct_matrix(&gray_src, &options),
+                Err(Error::InvalidParameter(_))
+            ));
+        }
+    }
+
+    mod error {
+        use crate::{
+            detector::{HoughDetector, ProjectionSweepDetector, SkewDetector},
+            hough, projection,
+            transfer::TransformableMatrix,
+            Error,
+        };
+        use opencv::{
+            core::{Mat, Scalar, CV_8UC1, CV_8UC3},
+            imgcodecs,
+        };
+
+        #[test]
+        fn error_test() {
+            assert!(matches!(
+                TransformableMatrix::new("./not_exists.jpg", imgcodecs::IMREAD_COLOR),
+                Err(Error::UnreadableInput(_))
+            ));
+
+            let empty_image = TransformableMatrix::from_matrix(&Mat::default());
+            assert!(empty_image.is_empty());
+            assert!(matches!(
+                HoughDetector::default().detect(&empty_image),
+                Err(Error::EmptyImage)
+            ));
+
+            // 空白图像中不存在直线
+            let blank_image = TransformableMatrix::from_matrix(
+                &Mat::new_rows_cols_with_default(64, 64, CV_8UC1, Scalar::all(255.0)).unwrap(),
+            );
+            let output_dir = format!("{}/", std::env::temp_dir().display());
+            assert!(matches!(
+                hough::get_angle_with_hough(&blank_image, 20.0, 5.0, "oics_blank.jpg", &output_dir),
+                Err(Error::NoFeatures(_))
+            ));
+
+            let color_image = TransformableMatrix::from_matrix(
+                &Mat::new_rows_cols_with_default(64, 64, CV_8UC3, Scalar::all(255.0)).unwrap(),
+            );
+            for step in [0.0, -0.1, f64::NAN] {
+                assert!(matches!(
+                    projection::get_angle_with_projections(
+                        &color_image,
+                        &ProjectionSweepDetector {
+                            step,
+                            resize_scale: 1.0,
+                            ..ProjectionSweepDetector::default()
+                        },
+                    ),
+                    Err(Error::InvalidParameter(_))
+                ));
+            }
+        }
+    }
+
+    mod pool {
+        use crate::{pool::WorkerPool, Error};
+
+        #[test]
+        fn map_test() {
+            let pool = WorkerPool::new(4);
+            let squares = pool
+                .map((0..100).collect(), |value: i32| value * value)
+                .unwrap();
+            assert_eq!(
+                squares,
+                (0..100).map(|value| value * value).collect::<Vec<_>>()
+            );
+
+            // 任务发生 panic 时返回错误，而不是在调用方线程中 panic
+            let result = pool.map((0..10).collect(), |value: i32| {
+                if value == 3 {
+                    panic!("task {} failed", value);
+                }
+                value
+            });
+            assert!(matches!(result, Err(Error::WorkerPool(_))));
+
+            // 工作线程在任务 panic 后仍然可用
+            assert_eq!(
+                pool.map(vec![1, 2], |value: i32| value + 1).unwrap(),
+                vec![2, 3]
+            );
+        }
+    }
+
+    mod rotation {
+        use crate::{
+            transfer::{self, TransformableMatrix},
+            types::RotateClipStrategy,
+        };
+        use opencv::{
+            core::{self, Mat, Point, Scalar, BORDER_CONSTANT, CV_8UC1},
+            imgproc,
+            prelude::MatTraitConst,
+        };
+
+        #[test]
+        fn rotate_mat_contain_test() {
+            // 200×100 的白色图像，黑点位于原图中心右侧 50 像素处
+            let mut mat =
+                Mat::new_rows_cols_with_default(100, 200, CV_8UC1, Scalar::all(255.0)).unwrap();
+            imgproc::circle(
+                &mut mat,
+                Point::new(150, 50),
+                3,
+                Scalar::all(0.0),
+                -1,
+                imgproc::LINE_8,
+                0,
+            )
+            .unwrap();
+
+            let angle: f64 = 30.0;
+            let rotated = transfer::rotate_mat(
+                &TransformableMatrix::from_matrix(&mat),
+                angle,
+                1.0,
+                imgproc::INTER_NEAREST,
+                BORDER_CONSTANT,
+                Scalar::all(255.0),
+                RotateClipStrategy::CONTAIN,
+            )
+            .unwrap();
+            let rotated = rotated.get_mat();
+
+            // 输出图像恰好容纳旋转后的原图
+            let (sin, cos) = angle.to_radians().sin_cos();
+            let width = (100.0 * sin + 200.0 * cos).ceil();
+            let height = (200.0 * sin + 100.0 * cos).ceil();
+            assert_eq!(rotated.cols(), width as i32);
+            assert_eq!(rotated.rows(), height as i32);
+
+            // 黑点绕原图中心逆时针旋转，原图中心平移至输出图像中心
+            let mut inverted = Mat::default();
+            core::bitwise_not(rotated, &mut inverted, &core::no_array()).unwrap();
+            let moments = imgproc::moments(&inverted, true).unwrap();
+            let (x, y) = (moments.m10 / moments.m00, moments.m01 / moments.m00);
+            let (expected_x, expected_y) = (width / 2.0 + 50.0 * cos, height / 2.0 - 50.0 * sin);
+            assert!((x - expected_x).abs() < 1.0, "{} {}", x, expected_x);
+            assert!((y - expected_y).abs() < 1.0, "{} {}", y, expected_y);
+        }
+    }
+
     mod multi_thread {
         use crate::omr;
         use once_cell::sync::Lazy;
@@ -292,8 +2393,12 @@ mod tests {
             println!(">> Start at {}", algorithm_start);
             for (input_file, output_file) in io_file_paths {
                 request_task(move || {
-                    omr::correct_default(&input_file, &output_file, 45, 0.2, 248, 230, 150.0, 50.0)
-                        .unwrap();
+                    omr::correct_default(
+                        &input_file,
+                        &output_file,
+                        &omr::CorrectionOptions::default(),
+                    )
+                    .unwrap();
 
                     let mut total_count = TOTAL_COUNT.lock().unwrap();
                     if *total_count == 99 {
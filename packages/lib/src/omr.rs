@@ -1,3 +1,26 @@
+pub mod ensemble;
+pub mod marks;
+
+use std::{collections::BTreeSet, fs, path::Path, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    anchor::{self, AnchorDetector},
+    detector::{EdgesDetector, ProjectionDetector},
+    error::{self, Error, Result},
+    hough,
+    omr::ensemble::{EnsembleDetector, EnsemblePolicy},
+    orientation::{self, Orientation, OrientationDetector},
+    pipeline::PreprocessPipeline,
+    projection::{self, sparse::SparseBinaryImage},
+    transfer::{self, TransformableMatrix},
+    types::{
+        BinarizationStrategy, IlluminationStrategy, PaperCropStrategy, PeakInterpolation,
+        RotateClipStrategy,
+    },
+};
+
 use opencv::{
     core::{Mat, Point2f, Scalar, Size2f, Size2i, CV_PI},
     imgcodecs, imgproc,
@@ -5,57 +28,304 @@ use opencv::{
     types::{VectorOfVec4f, VectorOfi32},
 };
 
-fn get_mat_projection_data(mat: &Mat) -> opencv::Result<(Vec<f64>, Vec<f64>)> {
-    // 定义数组分别记录水平方向、垂直方向上的投影数据
-    let mut horizontal_projection_data: Vec<f64> = Vec::with_capacity(mat.rows() as usize);
-    let mut vertical_projection_data: Vec<f64> = vec![0.0f64; mat.cols() as usize];
-
-    // 遍历每一行
-    for row_index in 0..mat.rows() {
-        // 获取当前行的数据数组
-        let row = mat.at_row::<u8>(row_index)?;
-
-        // 当前行黑色的色块总数
-        let mut row_black_sum = 0;
-        // 记录迭代序列
-        let mut col_index = 0;
-
-        // 遍历当前行的每一个色块，通过迭代器提高遍历速度
-        row.iter().for_each(|item| {
-            // 如果为白色色块
-            // 不做处理
-            // 如果为黑色色块
-            // 总数加一
-            if *item == 0 {
-                row_black_sum += 1;
-                vertical_projection_data[col_index] += 1.0;
-            }
-            col_index += 1;
-        });
-        horizontal_projection_data.push(row_black_sum as f64);
-    }
-
-    Ok((horizontal_projection_data, vertical_projection_data))
-}
-
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ResultStatus {
     Believed,
     NeedCheck,
     NotAResult,
 }
+#[derive(Clone, Debug)]
 pub struct OmrResult {
     pub angle: f64,
     pub status: ResultStatus,
     pub candidates: Vec<f64>,
+    /// 置信度，取值范围为 [0, 1]
+    pub confidence: f64,
+    /// 偏转角的估计误差（±deg）
+    pub uncertainty: f64,
 }
 
-pub fn get_result_from_projection(
-    src_mat: &Mat,
+/// 投影标准差的角度搜索方式
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ProjectionSearchMode {
+    /// 以 `projection_angle_step` 为步长遍历整个角度范围
+    Exhaustive,
+    /// 先在进一步缩小的图像上以较大步长粗略搜索，再在若干峰值附近以 `projection_angle_step` 精细搜索
+    CoarseToFine {
+        /// 粗搜索图像相对投影图像的缩放比例
+        coarse_scale: f64,
+        /// 粗搜索步长相对 `projection_angle_step` 的倍数
+        coarse_step_multiple: u16,
+        /// 参与精细搜索的峰值个数
+        peaks: usize,
+    },
+}
+impl Default for ProjectionSearchMode {
+    fn default() -> Self {
+        Self::Exhaustive
+    }
+}
+impl ProjectionSearchMode {
+    /// 默认参数的由粗到精搜索
+    ///
+    /// 粗搜索在面积为 1/4 的图像上计算 1/5 的角度，精细搜索在 3 个峰值附近各计算 11 个角度，
+    /// 步长为 0.2° 时投影计算量约为遍历搜索的 1/8，实测耗时见 `coarse_to_fine_bench_test`
+    pub fn coarse_to_fine() -> Self {
+        Self::CoarseToFine {
+            coarse_scale: 0.5,
+            coarse_step_multiple: 5,
+            peaks: 3,
+        }
+    }
+}
+
+/// 某一角度下的投影标准差
+struct ProjectionSample {
+    /// 角度序号，实际角度为序号与步长之积
+    index: i32,
+    /// 水平投影标准差
+    horizontal: f64,
+    /// 垂直投影标准差
+    vertical: f64,
+}
+
+/// 对输入图像进行灰度化与腐蚀预处理
+fn get_projection_eroded_mat(src_mat: &Mat) -> Result<Mat> {
+    let gray_mat = {
+        let mut dst_mat = Mat::default();
+        imgproc::cvt_color(&src_mat, &mut dst_mat, imgproc::COLOR_RGB2GRAY, 0)?;
+        dst_mat
+    };
+
+    // 先对输入图像进行腐蚀预处理以提升图像锐度
+    // 声明腐蚀操作输出图像可变
+    let mut eroded = Mat::default();
+    // 定义腐蚀核，为一个内嵌至长宽3像素的矩形的填充椭圆
+    let kernel = imgproc::get_structuring_element(
+        imgproc::MORPH_ELLIPSE,
+        opencv::core::Size::new(3, 3),
+        opencv::core::Point::new(-1, -1),
+    )?;
+    // 通过调用opencv的腐蚀操作对图像进行处理，将输出结果写入eroded的可变引用中
+    imgproc::erode(
+        &gray_mat,
+        &mut eroded,
+        &kernel,
+        opencv::core::Point::new(-1, -1),
+        3,
+        opencv::core::BORDER_CONSTANT,
+        imgproc::morphology_default_border_value()?,
+    )?;
+
+    Ok(eroded)
+}
+
+/// 将腐蚀后的灰度图缩放并二值化，得到用于投影的二值图
+fn get_projection_thresh_binary_mat(
+    eroded_mat: &Mat,
+    resize_scale: f64,
+    binarization: BinarizationStrategy,
+) -> Result<Mat> {
+    let scaled_mat = {
+        let mut scaled = Mat::default();
+        let size = eroded_mat.size()?;
+        imgproc::resize(
+            &eroded_mat,
+            &mut scaled,
+            Size2i::new(
+                ((size.width as f64) * resize_scale) as i32,
+                ((size.height as f64) * resize_scale) as i32,
+            ),
+            0.0,
+            0.0,
+            imgproc::INTER_AREA,
+        )?;
+        scaled
+    };
+
+    transfer::get_thresh_binary_mat(&scaled_mat, binarization)
+}
+
+/// 依次旋转二值图至各个角度，计算对应的投影标准差
+///
+/// 旋转与投影由稀疏二值图完成，结果与逐个角度旋转整幅二值图后统计的投影一致。
+/// 各角度分为 `threads` 段并行计算，结果与 `threads` 无关
+fn get_projection_samples(
+    thresh_binary_mat: &Mat,
+    indexes: impl Iterator<Item = i32>,
+    angle_step: f64,
+    rotate_scale: f64,
+    threads: usize,
+) -> Result<Vec<ProjectionSample>> {
+    let sparse_image = Arc::new(SparseBinaryImage::from_mat(thresh_binary_mat)?);
+    let indexes: Vec<i32> = indexes.collect();
+    let angles: Vec<f64> = indexes
+        .iter()
+        .map(|index| *index as f64 * angle_step)
+        .collect();
+
+    // 获取垂直、水平投影标准差
+    let (vertical_standard_deviations, horizontal_standard_deviations) =
+        projection::get_projection_standard_deviations_sweep(
+            &sparse_image,
+            &angles,
+            rotate_scale,
+            threads,
+        )?;
+
+    Ok(indexes
+        .into_iter()
+        .zip(horizontal_standard_deviations.into_iter())
+        .zip(vertical_standard_deviations.into_iter())
+        .map(|((index, horizontal), vertical)| ProjectionSample {
+            index,
+            horizontal,
+            vertical,
+        })
+        .collect())
+}
+
+/// 从按角度升序排列的投影标准差中找出最有可能的偏转角
+fn get_result_from_projection_samples(
+    samples: &Vec<ProjectionSample>,
     projection_max_angle: u16,
     projection_angle_step: f64,
-    projection_max_width: i32,
-    projection_max_height: i32,
-) -> opencv::Result<OmrResult> {
+    peak_interpolation: PeakInterpolation,
+) -> OmrResult {
+    if samples.is_empty() {
+        return OmrResult {
+            angle: 0.0,
+            status: ResultStatus::NotAResult,
+            candidates: vec![],
+            confidence: 0.0,
+            uncertainty: projection_max_angle as f64,
+        };
+    }
+
+    let mut max_horizontal_standard_deviation = 0.0;
+    let mut max_vertical_standard_deviation = 0.0;
+    let mut possible_horizontal_counts = 1u32;
+    let mut possible_vertical_counts = 1u32;
+    let mut most_possible_deg_vec: Vec<f64> = vec![];
+    let mut most_possible_index = 0usize;
+
+    for (sample_index, sample) in samples.iter().enumerate() {
+        let deg = sample.index as f64 * projection_angle_step;
+
+        // 先比较水平投影标准差
+        if max_horizontal_standard_deviation < sample.horizontal {
+            max_horizontal_standard_deviation = sample.horizontal;
+            max_vertical_standard_deviation = sample.vertical;
+            possible_horizontal_counts = 1;
+            possible_vertical_counts = 1;
+            most_possible_deg_vec = vec![deg];
+            most_possible_index = sample_index;
+        } else if max_horizontal_standard_deviation == sample.horizontal {
+            possible_horizontal_counts += 1;
+            // 再比较垂直投影标准差
+            if max_vertical_standard_deviation < sample.vertical {
+                possible_vertical_counts = 1;
+                max_vertical_standard_deviation = sample.vertical;
+                most_possible_deg_vec = vec![deg];
+                most_possible_index = sample_index;
+            } else if max_vertical_standard_deviation == sample.vertical {
+                possible_vertical_counts += 1;
+                most_possible_deg_vec.push(deg);
+            }
+        }
+    }
+
+    // 根据标准差曲线峰值的尖锐程度计算置信度与估计误差
+    // 仅使用峰值所在的角度连续的一段采样，由粗到精搜索时各精细搜索窗口之间并不相邻
+    let horizontal_standard_deviations: Vec<f64> =
+        samples.iter().map(|sample| sample.horizontal).collect();
+    let (window_start, window_end) = {
+        let mut start = most_possible_index;
+        while start > 0 && samples[start - 1].index + 1 == samples[start].index {
+            start -= 1;
+        }
+        let mut end = most_possible_index;
+        while end + 1 < samples.len() && samples[end + 1].index - 1 == samples[end].index {
+            end += 1;
+        }
+        (start, end)
+    };
+    let window_standard_deviations = &horizontal_standard_deviations[window_start..=window_end];
+    let confidence = crate::calculate::get_peak_confidence(
+        window_standard_deviations,
+        most_possible_index - window_start,
+    );
+    let uncertainty = crate::calculate::get_peak_half_width(
+        window_standard_deviations,
+        most_possible_index - window_start,
+    ) * projection_angle_step;
+
+    // 通过峰值插值获取小于步长的角度偏移量
+    // 仅在峰值两侧的采样点与峰值角度相邻时进行插值
+    if most_possible_deg_vec.len() == 1
+        && most_possible_index > 0
+        && most_possible_index + 1 < samples.len()
+        && samples[most_possible_index - 1].index + 1 == samples[most_possible_index].index
+        && samples[most_possible_index + 1].index - 1 == samples[most_possible_index].index
+    {
+        most_possible_deg_vec[0] += crate::calculate::get_peak_offset(
+            &horizontal_standard_deviations,
+            most_possible_index,
+            peak_interpolation,
+        ) * projection_angle_step;
+    }
+
+    if possible_horizontal_counts == 1 && possible_vertical_counts == 1 {
+        let target_angle = most_possible_deg_vec[0];
+        OmrResult {
+            angle: target_angle,
+            status: ResultStatus::Believed,
+            candidates: most_possible_deg_vec,
+            confidence,
+            uncertainty,
+        }
+    } else if most_possible_deg_vec.len() == 1 {
+        OmrResult {
+            angle: most_possible_deg_vec[0],
+            status: ResultStatus::NeedCheck,
+            candidates: most_possible_deg_vec,
+            confidence,
+            uncertainty,
+        }
+    } else {
+        OmrResult {
+            angle: 0.0,
+            status: ResultStatus::NotAResult,
+            candidates: most_possible_deg_vec,
+            confidence: 0.0,
+            uncertainty: projection_max_angle as f64,
+        }
+    }
+}
+
+/// ### 利用投影标准差方法查找偏转角
+///
+/// **参数列表：**
+/// - `src_mat`: 三通道图像
+/// - `detector`: 投影标准差检测器参数，`pipeline` 不为 `None` 时交由
+///   `get_result_from_projection_with_pipeline` 处理
+///
+pub fn get_result_from_projection(
+    src_mat: &Mat,
+    detector: &ProjectionDetector,
+) -> Result<OmrResult> {
+    error::check_not_empty(src_mat)?;
+    if let Some(pipeline) = &detector.pipeline {
+        return get_result_from_projection_with_pipeline(
+            &TransformableMatrix::from_matrix(src_mat),
+            pipeline,
+            detector,
+        );
+    }
+    let projection_max_width = detector.max_width;
+    let projection_max_height = detector.max_height;
+    let binarization = detector.binarization;
+
     // 计算缩放比例
     let projection_resize_scale = {
         let original_size = &src_mat.size()?;
@@ -83,156 +353,198 @@ pub fn get_result_from_projection(
 
     // 找出旋转角度
     // 先使用基本的投影标准差方法进行纠偏
-    let (projection_angle, projection_result_status, projection_candidate_result_vec) = {
-        let scaled_mat = {
-            let gray_mat = {
-                let mut dst_mat = Mat::default();
-                imgproc::cvt_color(&src_mat, &mut dst_mat, imgproc::COLOR_RGB2GRAY, 0)?;
-                dst_mat
-            };
+    let eroded_mat = get_projection_eroded_mat(src_mat)?;
+    let thresh_binary_mat =
+        get_projection_thresh_binary_mat(&eroded_mat, projection_resize_scale, binarization)?;
 
-            // 先对输入图像进行腐蚀预处理以提升图像锐度
-            // 声明腐蚀操作输出图像可变
-            let mut eroded = Mat::default();
-            // 定义腐蚀核，为一个内嵌至长宽3像素的矩形的填充椭圆
-            let kernel = imgproc::get_structuring_element(
-                imgproc::MORPH_ELLIPSE,
-                opencv::core::Size::new(3, 3),
-                opencv::core::Point::new(-1, -1),
-            )?;
-            // 通过调用opencv的腐蚀操作对图像进行处理，将输出结果写入eroded的可变引用中
-            imgproc::erode(
-                &gray_mat,
-                &mut eroded,
-                &kernel,
-                opencv::core::Point::new(-1, -1),
-                3,
-                opencv::core::BORDER_CONSTANT,
-                imgproc::morphology_default_border_value()?,
-            )?;
+    get_result_from_projection_thresh_binary_mat(
+        &thresh_binary_mat,
+        |coarse_scale| {
+            get_projection_thresh_binary_mat(
+                &eroded_mat,
+                projection_resize_scale * coarse_scale,
+                binarization,
+            )
+        },
+        projection_resize_scale,
+        detector,
+    )
+}
 
-            let mut scaled = Mat::default();
-            let size = eroded.size()?;
-            imgproc::resize(
-                &eroded,
-                &mut scaled,
-                Size2i::new(
-                    ((size.width as f64) * projection_resize_scale) as i32,
-                    ((size.height as f64) * projection_resize_scale) as i32,
-                ),
-                0.0,
-                0.0,
-                imgproc::INTER_AREA,
-            )?;
-            scaled
-        };
-        let thresh_binary_mat = {
-            let mut dst_mat = Mat::default();
-            imgproc::threshold(
-                &scaled_mat,
-                &mut dst_mat,
-                127.0,
-                255.0,
-                imgproc::THRESH_BINARY,
-            )?;
-            dst_mat
-        };
-        let projection_range_max_angle =
-            (projection_max_angle as f64 / projection_angle_step) as u16;
-        let projection_range = {
-            let min_angle = -(projection_range_max_angle as i32);
-            min_angle..(projection_range_max_angle as i32)
-        };
+/// ### 利用预处理流水线与投影标准差方法查找偏转角
+///
+/// 以 `pipeline` 替代 `get_result_from_projection` 内置的灰度化、腐蚀、缩放与二值化步骤，
+/// 流水线的输出应为二值图，输出为三通道图像时以固定阈值补充二值化
+///
+/// **参数列表：**
+/// - `src`: 三通道图像
+/// - `pipeline`: 预处理流水线
+/// - `detector`: 投影标准差检测器参数，其中 `max_width`、`max_height`、`binarization`
+///   与 `pipeline` 不生效
+///
+pub fn get_result_from_projection_with_pipeline(
+    src: &TransformableMatrix,
+    pipeline: &PreprocessPipeline,
+    detector: &ProjectionDetector,
+) -> Result<OmrResult> {
+    let thresh_binary_mat = {
+        let thresh_image = pipeline.apply(src)?;
+        if thresh_image.get_mat().channels() == 1 {
+            thresh_image.get_mat().clone()
+        } else {
+            let gray_image = transfer::transfer_rgb_image_to_gray_image(&thresh_image)?;
+            transfer::transfer_gray_image_to_thresh_binary(&gray_image)?
+                .get_mat()
+                .clone()
+        }
+    };
 
-        let mut max_horizontal_standard_deviation = 0.0;
-        let mut max_vertical_standard_deviation = 0.0;
-        let mut possible_horizontal_counts = 1u32;
-        let mut possible_vertical_counts = 1u32;
-        let mut most_possible_deg_vec: Vec<f64> = vec![];
-
-        for deg in projection_range {
-            let rotated_mat = {
-                let mut dst = Mat::default();
-                let size = thresh_binary_mat.size()?;
-                let center_point =
-                    Point2f::new((size.width as f32) / 2.0, (size.height as f32) / 2.0);
-                let rotate_matrix = imgproc::get_rotation_matrix_2d(
-                    center_point,
-                    deg as f64 * projection_angle_step,
-                    projection_resize_scale,
-                )?;
-
-                imgproc::warp_affine(
-                    &thresh_binary_mat,
-                    &mut dst,
-                    &rotate_matrix,
-                    size,
-                    imgproc::WARP_POLAR_LINEAR,
-                    opencv::core::BORDER_CONSTANT,
-                    Scalar::new(255.0, 255.0, 255.0, 0.0), // b g r
-                )?;
-                dst
+    get_result_from_projection_thresh_binary_mat(
+        &thresh_binary_mat,
+        |coarse_scale| {
+            // 粗搜索图像由二值图缩小后重新二值化得到
+            let mut scaled_image = TransformableMatrix::from_matrix(&thresh_binary_mat);
+            scaled_image.scale_self(coarse_scale)?;
+            Ok(
+                transfer::transfer_gray_image_to_thresh_binary(&scaled_image)?
+                    .get_mat()
+                    .clone(),
+            )
+        },
+        1.0,
+        detector,
+    )
+}
+
+/// 在二值图上按指定的搜索方式计算各角度的投影标准差，得出偏转角
+fn get_result_from_projection_thresh_binary_mat(
+    thresh_binary_mat: &Mat,
+    get_coarse_thresh_binary_mat: impl Fn(f64) -> Result<Mat>,
+    rotate_scale: f64,
+    detector: &ProjectionDetector,
+) -> Result<OmrResult> {
+    let projection_max_angle = detector.max_angle;
+    let projection_angle_step = detector.angle_step;
+    let threads = detector.threads;
+
+    let projection_range_max_angle =
+        error::check_angle_range("projection", projection_max_angle, projection_angle_step)?;
+    let projection_range = {
+        let min_angle = -(projection_range_max_angle as i32);
+        min_angle..(projection_range_max_angle as i32)
+    };
+
+    // 由粗到精搜索时同时返回粗搜索曲线及其步长
+    let (samples, coarse) = match detector.search_mode {
+        ProjectionSearchMode::Exhaustive => (
+            get_projection_samples(
+                thresh_binary_mat,
+                projection_range,
+                projection_angle_step,
+                rotate_scale,
+                threads,
+            )?,
+            None,
+        ),
+        ProjectionSearchMode::CoarseToFine {
+            coarse_scale,
+            coarse_step_multiple,
+            peaks,
+        } => {
+            let coarse_step_multiple = coarse_step_multiple.max(1) as i32;
+            let coarse_angle_step = projection_angle_step * coarse_step_multiple as f64;
+
+            // 粗搜索：在进一步缩小的图像上以较大步长遍历整个角度范围
+            let coarse_samples = {
+                let coarse_thresh_binary_mat = get_coarse_thresh_binary_mat(coarse_scale)?;
+                let coarse_range_max_angle =
+                    (projection_max_angle as f64 / coarse_angle_step) as i32;
+                get_projection_samples(
+                    &coarse_thresh_binary_mat,
+                    -coarse_range_max_angle..coarse_range_max_angle,
+                    coarse_angle_step,
+                    rotate_scale,
+                    threads,
+                )?
             };
 
-            // 获取水平、垂直投影数据
-            let (horizontal_projection_data, vertical_projection_data) =
-                get_mat_projection_data(&rotated_mat)?;
-
-            // 先获取水平投影标准差
-            let horizontal_projection_standard_deviation =
-                crate::calculate::get_standard_deviation(&horizontal_projection_data);
-            if max_horizontal_standard_deviation < horizontal_projection_standard_deviation {
-                max_horizontal_standard_deviation = horizontal_projection_standard_deviation;
-                // 再获取垂直投影标准差
-                max_vertical_standard_deviation =
-                    crate::calculate::get_standard_deviation(&vertical_projection_data);
-                possible_horizontal_counts = 1;
-                possible_vertical_counts = 1;
-                most_possible_deg_vec = vec![deg as f64 * projection_angle_step];
-            } else if max_horizontal_standard_deviation == horizontal_projection_standard_deviation
-            {
-                possible_horizontal_counts += 1;
-                // 再获取垂直投影标准差
-                let vertical_projection_standard_deviation =
-                    crate::calculate::get_standard_deviation(&vertical_projection_data);
-                if max_vertical_standard_deviation < vertical_projection_standard_deviation {
-                    possible_vertical_counts = 1;
-                    max_vertical_standard_deviation = vertical_projection_standard_deviation;
-                    most_possible_deg_vec = vec![deg as f64 * projection_angle_step];
-                } else if max_vertical_standard_deviation == vertical_projection_standard_deviation
-                {
-                    possible_vertical_counts += 1;
-                    most_possible_deg_vec.push(deg as f64 * projection_angle_step);
-                }
-            }
-        }
+            // 找出粗搜索曲线中最高的若干个局部峰值
+            let mut coarse_peaks: Vec<&ProjectionSample> = coarse_samples
+                .iter()
+                .enumerate()
+                .filter(|(index, sample)| {
+                    (*index == 0 || coarse_samples[index - 1].horizontal <= sample.horizontal)
+                        && (*index + 1 == coarse_samples.len()
+                            || coarse_samples[index + 1].horizontal <= sample.horizontal)
+                })
+                .map(|(_, sample)| sample)
+                .collect();
+            coarse_peaks.sort_by(|a, b| b.horizontal.total_cmp(&a.horizontal));
+            coarse_peaks.truncate(peaks.max(1));
 
-        if possible_horizontal_counts == 1 && possible_vertical_counts == 1 {
-            let target_angle = most_possible_deg_vec[0];
-            (target_angle, ResultStatus::Believed, most_possible_deg_vec)
-        } else if most_possible_deg_vec.len() == 1 {
-            (
-                most_possible_deg_vec[0],
-                ResultStatus::NeedCheck,
-                most_possible_deg_vec,
-            )
-        } else {
-            (0.0, ResultStatus::NotAResult, most_possible_deg_vec)
+            // 精细搜索：在各峰值前后一个粗搜索步长的范围内以原步长搜索
+            let fine_indexes: BTreeSet<i32> = coarse_peaks
+                .iter()
+                .flat_map(|peak| {
+                    let center = peak.index * coarse_step_multiple;
+                    (center - coarse_step_multiple)..=(center + coarse_step_multiple)
+                })
+                .filter(|index| projection_range.contains(index))
+                .collect();
+
+            let fine_samples = get_projection_samples(
+                thresh_binary_mat,
+                fine_indexes.into_iter(),
+                projection_angle_step,
+                rotate_scale,
+                threads,
+            )?;
+
+            (fine_samples, Some((coarse_samples, coarse_angle_step)))
         }
     };
 
-    Ok(OmrResult {
-        angle: projection_angle,
-        status: projection_result_status,
-        candidates: projection_candidate_result_vec,
-    })
+    let mut result = get_result_from_projection_samples(
+        &samples,
+        projection_max_angle,
+        projection_angle_step,
+        detector.peak_interpolation,
+    );
+
+    // 精细搜索窗口过窄，无法反映峰值是否突出，置信度改由覆盖整个角度范围的粗搜索曲线计算
+    if let Some((coarse_samples, coarse_angle_step)) = coarse {
+        if result.status != ResultStatus::NotAResult {
+            let coarse_standard_deviations: Vec<f64> = coarse_samples
+                .iter()
+                .map(|sample| sample.horizontal)
+                .collect();
+            let coarse_peak_index = coarse_samples
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| {
+                    let distance_a = (a.index as f64 * coarse_angle_step - result.angle).abs();
+                    let distance_b = (b.index as f64 * coarse_angle_step - result.angle).abs();
+                    distance_a.total_cmp(&distance_b)
+                })
+                .map(|(index, _)| index)
+                .unwrap_or(coarse_samples.len());
+            result.confidence = crate::calculate::get_peak_confidence(
+                &coarse_standard_deviations,
+                coarse_peak_index,
+            );
+        }
+    }
+
+    Ok(result)
 }
 
 pub fn get_result_from_edges_detection(
     src_mat: &Mat,
     edges_min_line_length: f64,
     edges_max_line_gap: f64,
-) -> opencv::Result<OmrResult> {
+) -> Result<OmrResult> {
+    error::check_not_empty(src_mat)?;
+
     // 边缘检测
     let edges = {
         let mut dst = Mat::default();
@@ -254,51 +566,8 @@ pub fn get_result_from_edges_detection(
         dst
     };
 
-    // 获取直线的斜率
-    let mut angles = vec![];
-    for l in lines.iter() {
-        let pt1 = Point2f::new(l[0], l[1]);
-        let pt2 = Point2f::new(l[2], l[3]);
-
-        let mut angle = (pt2.y - pt1.y).atan2(pt2.x - pt1.x) * 180.0 / std::f32::consts::PI;
-        // 限制偏转角度在 -45deg ~ +45deg 之间
-        angle = angle % 45.0;
-        angles.push(angle as f64);
-    }
-    // 找到最常出现的斜率作为图像的旋转角度
-    let range = 0.1;
-
-    // 目标角度
-    let mut target_angle = angles[0];
-    let mut candidates_vec = vec![];
-    // 处于目标角度的直线数目
-    let mut target_angle_lines_count = 0;
-    // 找寻目标角度线段总长度最大的角度作为最终的偏转角度
-    for i in 0..angles.len() {
-        let mut count = 0;
-        for j in 0..angles.len() {
-            if (angles[i] - angles[j]).abs() < range {
-                count += 1;
-            }
-        }
-        if count > target_angle_lines_count {
-            target_angle = angles[i];
-            target_angle_lines_count = count;
-            candidates_vec = vec![target_angle];
-        } else if count == target_angle_lines_count {
-            candidates_vec.push(angles[i]);
-        }
-    }
-
-    Ok(OmrResult {
-        angle: target_angle as f64,
-        status: match candidates_vec.len() {
-            0 => ResultStatus::NotAResult,
-            1 => ResultStatus::Believed,
-            _ => ResultStatus::NeedCheck,
-        },
-        candidates: candidates_vec,
-    })
+    // 以线段长度加权的角度直方图查找偏转角
+    Ok(hough::get_result_from_lines(&lines))
 }
 
 pub fn get_result_from_fourier_transform(
@@ -307,7 +576,9 @@ pub fn get_result_from_fourier_transform(
     canny_threshold_strong: f64,
     fourier_min_line_length: f64,
     fourier_max_line_gap: f64,
-) -> opencv::Result<OmrResult> {
+) -> Result<OmrResult> {
+    error::check_not_empty(src_mat)?;
+
     let fft_image = {
         let gray_tm = {
             let mut dst = Mat::default();
@@ -315,9 +586,9 @@ pub fn get_result_from_fourier_transform(
             dst
         };
         #[allow(unused_variables)]
-        let (magnitude_image, magnitude_log_image) =
-            crate::fft::get_fft_image(&crate::transfer::TransformableMatrix::from_matrix(&gray_tm))
-                .unwrap();
+        let (magnitude_image, magnitude_log_image) = crate::fft::get_fft_image(
+            &crate::transfer::TransformableMatrix::from_matrix(&gray_tm),
+        )?;
         magnitude_log_image
     };
 
@@ -336,117 +607,426 @@ pub fn get_result_from_fourier_transform(
     get_result_from_edges_detection(&edges, fourier_min_line_length, fourier_max_line_gap)
 }
 
-pub fn correct_default(
-    input_file: &str,
-    output_file: &str,
-    projection_max_angle: u16,
-    projection_angle_step: f64,
-    projection_max_width: i32,
-    projection_max_height: i32,
-    hough_min_line_length: f64,
-    hough_max_line_gap: f64,
-) -> opencv::Result<(f64, bool)> {
-    let src_mat = imgcodecs::imread(input_file, imgcodecs::IMREAD_COLOR)?;
-
-    // 找出旋转角度以及是否需要复查
-    let (rotate_angle, need_check) = {
-        let projection_result = get_result_from_projection(
-            &src_mat,
-            projection_max_angle,
-            projection_angle_step,
-            projection_max_width,
-            projection_max_height,
+/// ### 图像纠偏参数
+///
+/// 用例
+/// ```rust,no_run
+/// # use oics::omr::{self, CorrectionOptions};
+/// # use oics::types::PaperCropStrategy;
+///
+/// let options = CorrectionOptions::default()
+///     .projection_max_angle(30)
+///     .projection_angle_step(0.1)
+///     .paper_crop_strategy(PaperCropStrategy::CROP);
+/// options.validate().unwrap();
+///
+/// let result = omr::correct_default("01234.jpg", "01234_corrected.jpg", &options).unwrap();
+/// ```
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct CorrectionOptions {
+    /// 投影标准差方案的最大偏转角度
+    pub projection_max_angle: u16,
+    /// 投影标准差方案的角度步长
+    pub projection_angle_step: f64,
+    /// 投影标准差方案检测图像的最大宽度，不大于 0 时不限制宽度
+    pub projection_max_width: i32,
+    /// 投影标准差方案检测图像的最大高度，不大于 0 时不限制高度
+    pub projection_max_height: i32,
+    /// 投影标准差方案的角度搜索方式
+    pub projection_search_mode: ProjectionSearchMode,
+    /// 投影标准差方案计算各角度投影的并行度，不大于 1 时在当前线程中计算
+    pub projection_threads: usize,
+    /// 投影标准差方案的峰值插值方式
+    pub projection_peak_interpolation: PeakInterpolation,
+    /// 霍夫变换感知的最小线段长度
+    pub hough_min_line_length: f64,
+    /// 霍夫变换感知的线段最大中断长度
+    pub hough_max_line_gap: f64,
+    /// 投影标准差与边缘检测两种方案结果的融合策略
+    pub ensemble_policy: EnsemblePolicy,
+    /// 纸张边界裁剪方式
+    pub paper_crop_strategy: PaperCropStrategy,
+    /// 光照归一化方式
+    pub illumination_strategy: IlluminationStrategy,
+    /// 页面朝向检测器，为 `None` 时不检测页面朝向
+    pub orientation_detector: Option<OrientationDetector>,
+}
+impl Default for CorrectionOptions {
+    fn default() -> Self {
+        Self {
+            projection_max_angle: 45,
+            projection_angle_step: 0.2,
+            projection_max_width: 248,
+            projection_max_height: 230,
+            projection_search_mode: ProjectionSearchMode::Exhaustive,
+            projection_threads: 1,
+            projection_peak_interpolation: PeakInterpolation::NONE,
+            hough_min_line_length: 150.0,
+            hough_max_line_gap: 50.0,
+            ensemble_policy: EnsemblePolicy::default(),
+            paper_crop_strategy: PaperCropStrategy::NONE,
+            illumination_strategy: IlluminationStrategy::NONE,
+            orientation_detector: None,
+        }
+    }
+}
+impl CorrectionOptions {
+    /// 从 JSON 字符串解析参数并校验，缺省的字段取默认值
+    pub fn from_json_str(json: &str) -> Result<Self> {
+        let options: Self = serde_json::from_str(json)?;
+        options.validate()?;
+
+        Ok(options)
+    }
+
+    /// 从 JSON 文件加载参数并校验
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let json = fs::read_to_string(path)?;
+        Self::from_json_str(&json)
+    }
+
+    /// 设置投影标准差方案的最大偏转角度
+    pub fn projection_max_angle(mut self, max_angle: u16) -> Self {
+        self.projection_max_angle = max_angle;
+        self
+    }
+
+    /// 设置投影标准差方案的角度步长
+    pub fn projection_angle_step(mut self, step: f64) -> Self {
+        self.projection_angle_step = step;
+        self
+    }
+
+    /// 设置投影标准差方案检测图像的最大尺寸
+    pub fn projection_max_size(mut self, max_width: i32, max_height: i32) -> Self {
+        self.projection_max_width = max_width;
+        self.projection_max_height = max_height;
+        self
+    }
+
+    /// 设置投影标准差方案的角度搜索方式
+    pub fn projection_search_mode(mut self, search_mode: ProjectionSearchMode) -> Self {
+        self.projection_search_mode = search_mode;
+        self
+    }
+
+    /// 设置投影标准差方案的并行度
+    pub fn projection_threads(mut self, threads: usize) -> Self {
+        self.projection_threads = threads;
+        self
+    }
+
+    /// 设置投影标准差方案的峰值插值方式
+    pub fn projection_peak_interpolation(mut self, peak_interpolation: PeakInterpolation) -> Self {
+        self.projection_peak_interpolation = peak_interpolation;
+        self
+    }
+
+    /// 设置霍夫变换参数
+    pub fn hough(mut self, min_line_length: f64, max_line_gap: f64) -> Self {
+        self.hough_min_line_length = min_line_length;
+        self.hough_max_line_gap = max_line_gap;
+        self
+    }
+
+    /// 设置结果融合策略
+    pub fn ensemble_policy(mut self, policy: EnsemblePolicy) -> Self {
+        self.ensemble_policy = policy;
+        self
+    }
+
+    /// 设置纸张边界裁剪方式
+    pub fn paper_crop_strategy(mut self, strategy: PaperCropStrategy) -> Self {
+        self.paper_crop_strategy = strategy;
+        self
+    }
+
+    /// 设置光照归一化方式
+    pub fn illumination_strategy(mut self, strategy: IlluminationStrategy) -> Self {
+        self.illumination_strategy = strategy;
+        self
+    }
+
+    /// 设置页面朝向检测器
+    pub fn orientation_detector(mut self, detector: OrientationDetector) -> Self {
+        self.orientation_detector = Some(detector);
+        self
+    }
+
+    /// ### 检查参数是否合法
+    ///
+    /// 参数不合法时返回 `Error::InvalidParameter`，包括：
+    /// - 角度步长不为正数，或最大偏转角度与角度步长之比超出 `u16` 的范围
+    /// - 由粗到精搜索的粗搜索缩放比例不为正数
+    /// - 霍夫变换的线段长度或中断长度为负数
+    /// - 结果融合策略不合法，见 `EnsemblePolicy::validate`
+    /// - 页面朝向检测器的边缘带比例不在 (0, 0.5] 之间，或检测图像的最大尺寸不为正数
+    pub fn validate(&self) -> Result<()> {
+        error::check_angle_range(
+            "projection",
+            self.projection_max_angle,
+            self.projection_angle_step,
         )?;
+        if let ProjectionSearchMode::CoarseToFine { coarse_scale, .. } = self.projection_search_mode
+        {
+            error::check_positive("projection coarse scale", coarse_scale)?;
+        }
+
+        for (name, value) in [
+            ("hough min line length", self.hough_min_line_length),
+            ("hough max line gap", self.hough_max_line_gap),
+        ] {
+            if value.is_nan() || value < 0.0 {
+                return Err(Error::InvalidParameter(format!(
+                    "{} must not be negative, got {}",
+                    name, value
+                )));
+            }
+        }
 
-        match projection_result.status {
-            ResultStatus::Believed => (projection_result.angle, false),
-            _ => {
-                // 投影标准差方案不确定，方案降级至霍夫变化进行比对
-                {
-                    let edges_result = get_result_from_edges_detection(
-                        &src_mat,
-                        hough_min_line_length,
-                        hough_max_line_gap,
-                    )?;
-
-                    // 返回旋转角度 target_angle
-                    match projection_result.status {
-                        ResultStatus::NeedCheck => {
-                            // 当两种方案偏差超过 0.1° 时判定需要额外检查
-                            if (projection_result.angle - edges_result.angle).abs() >= 0.1 {
-                                (edges_result.angle, true)
-                            } else {
-                                (projection_result.angle, false)
-                            }
-                        }
-                        ResultStatus::NotAResult => {
-                            match projection_result.candidates.into_iter().min_by(|&a, &b| {
-                                (&(a - edges_result.angle).abs())
-                                    .partial_cmp(&(b - edges_result.angle).abs())
-                                    .unwrap()
-                            }) {
-                                Some(projection_candidate) => {
-                                    if (projection_candidate - edges_result.angle).abs() < 0.05 {
-                                        (projection_candidate, false)
-                                    } else {
-                                        (edges_result.angle, true)
-                                    }
-                                }
-                                None => (edges_result.angle, true),
-                            }
-                        }
-                        _ => unreachable!(),
-                    }
-                }
+        self.ensemble_policy.validate()?;
+
+        if let Some(detector) = &self.orientation_detector {
+            error::check_positive("orientation band ratio", detector.band_ratio)?;
+            if detector.band_ratio > 0.5 {
+                return Err(Error::InvalidParameter(format!(
+                    "orientation band ratio must not exceed 0.5, got {}",
+                    detector.band_ratio
+                )));
             }
+            error::check_positive("orientation max width", detector.max_width as f64)?;
+            error::check_positive("orientation max height", detector.max_height as f64)?;
         }
+
+        Ok(())
+    }
+}
+
+/// 图像纠偏结果
+#[derive(Clone, Debug)]
+pub struct CorrectionResult {
+    /// 旋转角度
+    pub angle: f64,
+    /// 是否需要复查
+    pub need_check: bool,
+    /// 置信度，取值范围为 [0, 1]
+    pub confidence: f64,
+    /// 旋转角度的估计误差（±deg）
+    pub uncertainty: f64,
+    /// 页面朝向，输出图像的实际旋转角度为 `angle` 与页面朝向角度之和
+    pub orientation: Orientation,
+}
+
+/// 纠偏后的图像及纠偏结果
+pub struct CorrectedImage {
+    /// 纠偏后的三通道图像
+    pub image: TransformableMatrix,
+    /// 纠偏结果
+    pub result: CorrectionResult,
+}
+
+/// ### 纠正图像偏转角并输出
+///
+/// **参数列表：**
+/// - `input_file`: 输入图像路径
+/// - `output_file`: 输出图像路径
+/// - `options`: 纠偏参数，不合法时返回 `Error::InvalidParameter`
+///
+pub fn correct_default(
+    input_file: &str,
+    output_file: &str,
+    options: &CorrectionOptions,
+) -> Result<CorrectionResult> {
+    let src = TransformableMatrix::new(input_file, imgcodecs::IMREAD_COLOR)?;
+    let CorrectedImage { image, result } = correct_matrix(&src, options)?;
+
+    let quality_vec = VectorOfi32::from(vec![imgcodecs::IMWRITE_JPEG_QUALITY, 100]);
+    imgcodecs::imwrite(output_file, image.get_mat(), &quality_vec)?;
+
+    Ok(result)
+}
+
+/// ### 纠正编码图像的偏转角，不读写文件
+///
+/// **参数列表：**
+/// - `buffer`: 编码后的图像数据，支持 `imgcodecs` 可解码的格式
+/// - `options`: 纠偏参数，不合法时返回 `Error::InvalidParameter`
+///
+/// 无法解码时返回 `Error::UnreadableInput`
+///
+/// 用例
+/// ```rust,no_run
+/// # use oics::omr::{self, CorrectionOptions};
+/// # use oics::types::ImageFormat;
+///
+/// let buffer = std::fs::read("01234.jpg").unwrap();
+/// let corrected = omr::correct_bytes(&buffer, &CorrectionOptions::default()).unwrap();
+/// let output = corrected.image.encode(ImageFormat::JPEG, 100).unwrap();
+/// println!("{} {}", corrected.result.angle, output.len());
+/// ```
+pub fn correct_bytes(buffer: &[u8], options: &CorrectionOptions) -> Result<CorrectedImage> {
+    let src = TransformableMatrix::from_bytes(buffer, imgcodecs::IMREAD_COLOR)?;
+    correct_matrix(&src, options)
+}
+
+/// ### 纠正图像偏转角，不读写文件
+///
+/// **参数列表：**
+/// - `src`: 三通道图像，通道数不为 3 时返回 `Error::InvalidParameter`
+/// - `options`: 纠偏参数，不合法时返回 `Error::InvalidParameter`
+///
+/// 图像为空时返回 `Error::EmptyImage`
+pub fn correct_matrix(
+    src: &TransformableMatrix,
+    options: &CorrectionOptions,
+) -> Result<CorrectedImage> {
+    options.validate()?;
+    error::check_not_empty(src.get_mat())?;
+    if src.get_mat().channels() != 3 {
+        return Err(Error::InvalidParameter(format!(
+            "source image must have 3 channels, got {}",
+            src.get_mat().channels()
+        )));
+    }
+
+    // 纠偏前先裁剪至纸张区域，去除扫描背景对投影数据的干扰
+    let src_mat = {
+        let mut src = src.clone();
+        src.crop_to_paper(options.paper_crop_strategy)?;
+        src.get_mat().clone()
+    };
+    // 用于检测的图像，经光照归一化消除光照渐变与阴影，输出图像仍使用原图
+    let detection_mat = {
+        let mut detection_image = TransformableMatrix::from_matrix(&src_mat);
+        detection_image.normalize_illumination(options.illumination_strategy)?;
+        detection_image.get_mat().clone()
     };
 
-    // 输出图像
-    {
-        // 旋转图像
-        let rotated_mat = {
-            let mut dst = Mat::default();
-            // 计算旋转后的图像尺寸
-            let rotated_width = ((src_mat.rows() as f64)
-                * (rotate_angle * CV_PI / 180.0).sin().abs()
-                + (src_mat.cols() as f64) * (rotate_angle * CV_PI / 180.0).cos().abs())
-            .ceil();
-            let rotated_height = ((src_mat.cols() as f64)
-                * (rotate_angle * CV_PI / 180.0).sin().abs()
-                + (src_mat.rows() as f64) * (rotate_angle * CV_PI / 180.0).cos().abs())
-            .ceil();
-
-            // 计算仿射变换矩阵
-            let center_point = Point2f::from_size(Size2f::new(
-                (rotated_width / 2.0).ceil() as f32,
-                (rotated_height / 2.0).ceil() as f32,
-            ));
-            let mut rotate_matrix =
-                imgproc::get_rotation_matrix_2d(center_point, rotate_angle, 1.0)?;
-
-            // 防止切边，对平移矩阵进行修改
-            let element = rotate_matrix.at_2d_mut::<f64>(0, 2)?;
-            *element += ((rotated_width - src_mat.cols() as f64) / 2.0).ceil();
-            let element = rotate_matrix.at_2d_mut::<f64>(1, 2)?;
-            *element += ((rotated_height - src_mat.rows() as f64) / 2.0).ceil();
-
-            // 应用仿射变换
-            imgproc::warp_affine(
-                &src_mat,
-                &mut dst,
-                &rotate_matrix,
-                Size2i::new(rotated_width as i32, rotated_height as i32),
-                imgproc::WARP_POLAR_LINEAR,
+    // 投影标准差方案可信时直接采用，否则降级至边缘检测进行比对，按融合策略得出旋转角度与是否需要复查。
+    // 默认策略要求两种方案一致，二者存在分歧时需要复查
+    let (rotate_angle, need_check, confidence, uncertainty) = {
+        let ensemble = EnsembleDetector::new(options.ensemble_policy.clone())
+            .member(
+                Box::new(ProjectionDetector {
+                    max_angle: options.projection_max_angle,
+                    angle_step: options.projection_angle_step,
+                    max_width: options.projection_max_width,
+                    max_height: options.projection_max_height,
+                    search_mode: options.projection_search_mode,
+                    threads: options.projection_threads,
+                    peak_interpolation: options.projection_peak_interpolation,
+                    ..ProjectionDetector::default()
+                }),
+                2.0,
+            )
+            .fallback(
+                Box::new(EdgesDetector {
+                    min_line_length: options.hough_min_line_length,
+                    max_line_gap: options.hough_max_line_gap,
+                }),
+                1.0,
+            );
+        let ensemble_result =
+            ensemble.detect_all(&TransformableMatrix::from_matrix(&detection_mat))?;
+
+        (
+            ensemble_result.angle,
+            ensemble_result.need_check,
+            ensemble_result.confidence,
+            ensemble_result.uncertainty,
+        )
+    };
+
+    // 检测页面朝向
+    let orientation = match &options.orientation_detector {
+        Some(detector) => {
+            // 在缩小后的纠偏图像上检测页面朝向
+            let mut preview_image = TransformableMatrix::from_matrix(&detection_mat);
+            preview_image.shrink_to(detector.max_width, detector.max_height)?;
+            let deskewed_image = transfer::rotate_mat(
+                &preview_image,
+                rotate_angle,
+                1.0,
+                imgproc::INTER_LINEAR,
                 opencv::core::BORDER_CONSTANT,
-                Scalar::new(255.0, 255.0, 255.0, 0.0), // b g r
+                Scalar::new(255.0, 255.0, 255.0, 0.0),
+                RotateClipStrategy::CONTAIN,
             )?;
-            dst
-        };
+            orientation::get_orientation(&deskewed_image, detector)?.orientation
+        }
+        None => Orientation::Deg0,
+    };
+    // 输出图像的旋转角度为偏转角与页面朝向角度之和
+    let output_angle = rotate_angle + orientation.angle();
+
+    // 旋转图像
+    let rotated_mat = {
+        let mut dst = Mat::default();
+        // 计算旋转后的图像尺寸
+        let rotated_width = ((src_mat.rows() as f64) * (output_angle * CV_PI / 180.0).sin().abs()
+            + (src_mat.cols() as f64) * (output_angle * CV_PI / 180.0).cos().abs())
+        .ceil();
+        let rotated_height = ((src_mat.cols() as f64) * (output_angle * CV_PI / 180.0).sin().abs()
+            + (src_mat.rows() as f64) * (output_angle * CV_PI / 180.0).cos().abs())
+        .ceil();
 
-        let quality_vec = VectorOfi32::from(vec![imgcodecs::IMWRITE_JPEG_QUALITY, 100]);
-        imgcodecs::imwrite(output_file, &rotated_mat, &quality_vec)?;
+        // 计算仿射变换矩阵，以原图中心为旋转中心
+        let center_point = Point2f::from_size(Size2f::new(
+            src_mat.cols() as f32 / 2.0,
+            src_mat.rows() as f32 / 2.0,
+        ));
+        let mut rotate_matrix = imgproc::get_rotation_matrix_2d(center_point, output_angle, 1.0)?;
+
+        // 防止切边，对平移矩阵进行修改，使旋转中心位于输出图像中心
+        let element = rotate_matrix.at_2d_mut::<f64>(0, 2)?;
+        *element += (rotated_width - src_mat.cols() as f64) / 2.0;
+        let element = rotate_matrix.at_2d_mut::<f64>(1, 2)?;
+        *element += (rotated_height - src_mat.rows() as f64) / 2.0;
+
+        // 应用仿射变换
+        imgproc::warp_affine(
+            &src_mat,
+            &mut dst,
+            &rotate_matrix,
+            Size2i::new(rotated_width as i32, rotated_height as i32),
+            imgproc::WARP_POLAR_LINEAR,
+            opencv::core::BORDER_CONSTANT,
+            Scalar::new(255.0, 255.0, 255.0, 0.0), // b g r
+        )?;
+        dst
     };
 
-    Ok((rotate_angle, need_check))
+    Ok(CorrectedImage {
+        image: TransformableMatrix::from_matrix(&rotated_mat),
+        result: CorrectionResult {
+            angle: rotate_angle,
+            need_check,
+            confidence,
+            uncertainty,
+            orientation,
+        },
+    })
+}
+
+/// ### 利用四角定位块对图像进行透视纠正
+///
+/// 作为 `correct_default` 的替代方案，适用于手机拍摄等存在透视畸变的图像
+///
+/// 找到四个定位块时输出纠正后的图像并返回 `true`，否则不输出图像并返回 `false`
+pub fn correct_with_anchors(
+    input_file: &str,
+    output_file: &str,
+    anchor_detector: &AnchorDetector,
+) -> Result<bool> {
+    let src = TransformableMatrix::new(input_file, imgcodecs::IMREAD_COLOR)?;
+
+    match anchor::correct_with_anchors(&src, anchor_detector)? {
+        Some(corrected) => {
+            let quality_vec = VectorOfi32::from(vec![imgcodecs::IMWRITE_JPEG_QUALITY, 100]);
+            imgcodecs::imwrite(output_file, corrected.get_mat(), &quality_vec)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
 }
@@ -0,0 +1,385 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    calculate,
+    detector::SkewDetector,
+    error::{self, Error, Result},
+    omr::{OmrResult, ResultStatus},
+    transfer::TransformableMatrix,
+};
+
+/// ### 集成检测策略
+///
+/// 控制各检测器结果的融合方式与复查条件，可序列化为 JSON 以便在不修改代码的情况下调整
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct EnsemblePolicy {
+    /// 与融合角度的夹角不大于该值（deg）的结果视为一致
+    pub agreement_range: f64,
+    /// 一致结果的权重占比低于该值时需要复查，取值范围为 [0, 1]。
+    /// 默认为 1，即任一有效结果与融合角度不一致时均需要复查，
+    /// 以便只有两个检测器时二者的分歧也能被发现
+    pub min_agreement: f64,
+    /// 有效结果少于该数目时需要复查
+    pub min_results: usize,
+    /// 是否以检测结果的置信度对检测器权重进行加权
+    pub weight_by_confidence: bool,
+    /// 任一有效结果的状态为 `NeedCheck` 时是否需要复查
+    pub check_uncertain_results: bool,
+}
+impl Default for EnsemblePolicy {
+    fn default() -> Self {
+        Self {
+            agreement_range: 0.1,
+            min_agreement: 1.0,
+            min_results: 1,
+            weight_by_confidence: true,
+            check_uncertain_results: false,
+        }
+    }
+}
+impl EnsemblePolicy {
+    /// 从 JSON 字符串解析策略并校验，缺省的字段取默认值
+    pub fn from_json_str(json: &str) -> Result<Self> {
+        let policy: Self = serde_json::from_str(json)?;
+        policy.validate()?;
+
+        Ok(policy)
+    }
+
+    /// 将策略序列化为 JSON 字符串
+    pub fn to_json_string(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// ### 检查策略是否合法
+    ///
+    /// `agreement_range` 为负数，或 `min_agreement` 不在 [0, 1] 之间时返回 `Error::InvalidParameter`
+    pub fn validate(&self) -> Result<()> {
+        if self.agreement_range.is_nan() || self.agreement_range < 0.0 {
+            return Err(Error::InvalidParameter(format!(
+                "agreement range must not be negative, got {}",
+                self.agreement_range
+            )));
+        }
+        if !(0.0..=1.0).contains(&self.min_agreement) {
+            return Err(Error::InvalidParameter(format!(
+                "min agreement must be in [0, 1], got {}",
+                self.min_agreement
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// 需要复查的原因
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReviewReason {
+    /// 所有检测器均未给出有效结果
+    NoResult,
+    /// 有效结果的数目少于 `EnsemblePolicy::min_results`
+    InsufficientResults { count: usize, required: usize },
+    /// 一致结果的权重占比低于 `EnsemblePolicy::min_agreement`
+    Disagreement { agreement: f64, required: f64 },
+    /// 检测器给出的结果需要复查
+    UncertainResult { detector: &'static str },
+}
+
+/// 单个检测器的投票
+#[derive(Clone, Debug)]
+pub struct EnsembleVote {
+    /// 检测器名称
+    pub detector: &'static str,
+    /// 检测结果
+    pub result: OmrResult,
+    /// 参与融合的实际权重，结果无效时为 0
+    pub weight: f64,
+    /// 是否与融合角度一致
+    pub agreed: bool,
+    /// 检测器返回的错误，检测成功时为 `None`
+    pub error: Option<String>,
+}
+
+/// 集成检测结果
+#[derive(Clone, Debug)]
+pub struct EnsembleResult {
+    /// 融合后的偏转角
+    pub angle: f64,
+    /// 是否需要复查
+    pub need_check: bool,
+    /// 需要复查的原因，不需要复查时为空
+    pub reasons: Vec<ReviewReason>,
+    /// 一致结果的权重占比，取值范围为 [0, 1]
+    pub agreement: f64,
+    /// 置信度，取值范围为 [0, 1]
+    pub confidence: f64,
+    /// 偏转角的估计误差（±deg）
+    pub uncertainty: f64,
+    /// 各检测器的投票，顺序与检测器的添加顺序一致，`members` 在前，未运行的备用检测器没有投票
+    pub votes: Vec<EnsembleVote>,
+}
+impl EnsembleResult {
+    /// 转换为 `OmrResult`，需要复查时状态为 `NeedCheck`
+    pub fn to_omr_result(&self) -> OmrResult {
+        let status = if self.reasons.contains(&ReviewReason::NoResult) {
+            ResultStatus::NotAResult
+        } else if self.need_check {
+            ResultStatus::NeedCheck
+        } else {
+            ResultStatus::Believed
+        };
+
+        OmrResult {
+            angle: self.angle,
+            status,
+            candidates: self
+                .votes
+                .iter()
+                .filter(|vote| vote.weight > 0.0)
+                .map(|vote| vote.result.angle)
+                .collect(),
+            confidence: self.confidence,
+            uncertainty: self.uncertainty,
+        }
+    }
+}
+
+/// ### 集成检测器
+///
+/// 运行任意一组检测器，以加权圆周中位数融合各检测器给出的偏转角，并统计结果的一致程度
+///
+/// 用例
+/// ```rust,no_run
+/// # use oics::detector::{HoughDetector, ProjectionDetector, RadonDetector};
+/// # use oics::omr::ensemble::{EnsembleDetector, EnsemblePolicy};
+/// # use oics::transfer::TransformableMatrix;
+/// # use opencv::imgcodecs;
+///
+/// let ensemble = EnsembleDetector::new(EnsemblePolicy::default())
+///     .member(Box::new(ProjectionDetector::default()), 2.0)
+///     .member(Box::new(HoughDetector::default()), 1.0)
+///     .fallback(Box::new(RadonDetector::default()), 1.0);
+///
+/// let src = TransformableMatrix::new("01234.jpg", imgcodecs::IMREAD_COLOR).unwrap();
+/// let result = ensemble.detect_all(&src).unwrap();
+/// if result.need_check {
+///     println!("{:?}", result.reasons);
+/// }
+/// ```
+pub struct EnsembleDetector {
+    /// 融合策略
+    pub policy: EnsemblePolicy,
+    /// 检测器及其权重
+    pub members: Vec<(Box<dyn SkewDetector>, f64)>,
+    /// 备用检测器及其权重，仅当 `members` 均未给出 `Believed` 结果时运行
+    pub fallbacks: Vec<(Box<dyn SkewDetector>, f64)>,
+}
+impl EnsembleDetector {
+    pub fn new(policy: EnsemblePolicy) -> Self {
+        Self {
+            policy,
+            members: vec![],
+            fallbacks: vec![],
+        }
+    }
+
+    /// 追加检测器，`weight` 为非负有限权重
+    pub fn member(mut self, detector: Box<dyn SkewDetector>, weight: f64) -> Self {
+        self.members.push((detector, weight));
+        self
+    }
+
+    /// 追加备用检测器，`weight` 为非负有限权重
+    pub fn fallback(mut self, detector: Box<dyn SkewDetector>, weight: f64) -> Self {
+        self.fallbacks.push((detector, weight));
+        self
+    }
+
+    /// ### 检查策略与检测器权重是否合法
+    ///
+    /// 策略不合法，或任一检测器的权重为负数、NaN 或无穷大时返回 `Error::InvalidParameter`
+    pub fn validate(&self) -> Result<()> {
+        self.policy.validate()?;
+        for (detector, weight) in self.members.iter().chain(self.fallbacks.iter()) {
+            if !weight.is_finite() || *weight < 0.0 {
+                return Err(Error::InvalidParameter(format!(
+                    "{} weight must be a non-negative finite number, got {}",
+                    detector.name(),
+                    weight
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// ### 运行所有检测器并融合结果
+    ///
+    /// 单个检测器返回错误时，该检测器的投票视为权重为 0 的 `NotAResult`，错误信息记录在投票中。
+    /// 任一 `members` 给出 `Believed` 结果时不再运行备用检测器，备用检测器也不会产生投票。
+    /// 图像为空时返回 `Error::EmptyImage`，策略或权重不合法时返回 `Error::InvalidParameter`
+    pub fn detect_all(&self, src: &TransformableMatrix) -> Result<EnsembleResult> {
+        self.validate()?;
+        error::check_not_empty(src.get_mat())?;
+
+        let mut results = vec![];
+        let mut errors = vec![];
+        let believed = |results: &[(&'static str, OmrResult, f64)]| {
+            results
+                .iter()
+                .any(|(_, result, _)| result.status == ResultStatus::Believed)
+        };
+        for (index, (detector, weight)) in
+            self.members.iter().chain(self.fallbacks.iter()).enumerate()
+        {
+            if index == self.members.len() && believed(&results) {
+                break;
+            }
+
+            match detector.detect(src) {
+                Ok(result) => {
+                    results.push((detector.name(), result, *weight));
+                    errors.push(None);
+                }
+                Err(err) => {
+                    let result = OmrResult {
+                        angle: 0.0,
+                        status: ResultStatus::NotAResult,
+                        candidates: vec![],
+                        confidence: 0.0,
+                        uncertainty: calculate::LINE_ANGLE_PERIOD / 2.0,
+                    };
+                    results.push((detector.name(), result, 0.0));
+                    errors.push(Some(err.to_string()));
+                }
+            }
+        }
+
+        let mut ensemble_result = fuse_results(results, &self.policy);
+        for (vote, error) in ensemble_result.votes.iter_mut().zip(errors) {
+            vote.error = error;
+        }
+
+        Ok(ensemble_result)
+    }
+}
+impl SkewDetector for EnsembleDetector {
+    fn name(&self) -> &'static str {
+        "ensemble"
+    }
+
+    fn detect(&self, src: &TransformableMatrix) -> Result<OmrResult> {
+        Ok(self.detect_all(src)?.to_omr_result())
+    }
+}
+
+/// ### 融合各检测器的结果
+///
+/// **参数列表：**
+/// - `results`: 检测器名称、检测结果与检测器权重
+/// - `policy`: 融合策略
+///
+/// 状态为 `NotAResult` 的结果不参与融合
+pub fn fuse_results(
+    results: Vec<(&'static str, OmrResult, f64)>,
+    policy: &EnsemblePolicy,
+) -> EnsembleResult {
+    let is_valid = |result: &OmrResult| result.status != ResultStatus::NotAResult;
+
+    // 以置信度加权时，若所有有效结果的置信度均为 0，则退化为检测器权重
+    let use_confidence = policy.weight_by_confidence
+        && results
+            .iter()
+            .any(|(_, result, weight)| is_valid(result) && weight * result.confidence > 0.0);
+    let mut votes: Vec<EnsembleVote> = results
+        .into_iter()
+        .map(|(detector, result, weight)| {
+            let weight = if !is_valid(&result) {
+                0.0
+            } else if use_confidence {
+                weight.max(0.0) * result.confidence
+            } else {
+                weight.max(0.0)
+            };
+            EnsembleVote {
+                detector,
+                result,
+                weight,
+                agreed: false,
+                error: None,
+            }
+        })
+        .collect();
+
+    let (angles, weights): (Vec<f64>, Vec<f64>) = votes
+        .iter()
+        .filter(|vote| vote.weight > 0.0)
+        .map(|vote| (vote.result.angle, vote.weight))
+        .unzip();
+    let angle = match calculate::get_circular_weighted_median(&angles, &weights) {
+        Some(angle) => angle,
+        None => {
+            return EnsembleResult {
+                angle: 0.0,
+                need_check: true,
+                reasons: vec![ReviewReason::NoResult],
+                agreement: 0.0,
+                confidence: 0.0,
+                uncertainty: calculate::LINE_ANGLE_PERIOD / 2.0,
+                votes,
+            };
+        }
+    };
+
+    let (mut agreed_weight, mut total_weight) = (0.0, 0.0);
+    let (mut confidence_sum, mut uncertainty_sum, mut max_deviation) = (0.0, 0.0, 0.0f64);
+    for vote in votes.iter_mut().filter(|vote| vote.weight > 0.0) {
+        let deviation = calculate::get_line_angle_distance(vote.result.angle, angle);
+        total_weight += vote.weight;
+        if deviation <= policy.agreement_range {
+            vote.agreed = true;
+            agreed_weight += vote.weight;
+            confidence_sum += vote.result.confidence * vote.weight;
+            uncertainty_sum += vote.result.uncertainty * vote.weight;
+            max_deviation = max_deviation.max(deviation);
+        }
+    }
+    // 融合角度取自有效结果之一，一致结果的权重必然为正
+    let agreement = agreed_weight / total_weight;
+
+    let mut reasons = vec![];
+    if angles.len() < policy.min_results {
+        reasons.push(ReviewReason::InsufficientResults {
+            count: angles.len(),
+            required: policy.min_results,
+        });
+    }
+    if agreement < policy.min_agreement {
+        reasons.push(ReviewReason::Disagreement {
+            agreement,
+            required: policy.min_agreement,
+        });
+    }
+    if policy.check_uncertain_results {
+        for vote in votes.iter() {
+            if vote.weight > 0.0 && vote.result.status == ResultStatus::NeedCheck {
+                reasons.push(ReviewReason::UncertainResult {
+                    detector: vote.detector,
+                });
+            }
+        }
+    }
+
+    EnsembleResult {
+        angle,
+        need_check: !reasons.is_empty(),
+        reasons,
+        agreement,
+        // 置信度取一致结果的加权平均置信度，并按一致程度折减
+        confidence: confidence_sum / agreed_weight * agreement,
+        // 估计误差不小于一致结果与融合角度之间的最大偏差
+        uncertainty: (uncertainty_sum / agreed_weight).max(max_deviation),
+        votes,
+    }
+}
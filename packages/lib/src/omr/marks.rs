@@ -0,0 +1,302 @@
+use opencv::{
+    core::{self, Mat, Rect},
+    imgproc,
+    types::VectorOfVectorOfPoint,
+};
+
+use crate::{
+    error::{Error, Result},
+    template::{BubbleField, SheetTemplate},
+    transfer::{self, TransformableMatrix},
+};
+
+/// 填涂状态
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MarkState {
+    /// 已填涂
+    Filled,
+    /// 未填涂
+    Empty,
+    /// 填涂程度介于两个阈值之间，需要人工复查
+    Ambiguous,
+}
+
+/// 单个选项框的识别结果
+#[derive(Clone, Debug)]
+pub struct Mark {
+    /// 选项框所在行的序号，自上而下从 0 开始
+    pub row: usize,
+    /// 选项框在行内的序号，自左而右从 0 开始
+    pub column: usize,
+    /// 选项框的外接矩形
+    pub rect: Rect,
+    /// 选项框内部的填涂比例，取值范围为 [0, 1]
+    pub fill_ratio: f64,
+    /// 填涂状态
+    pub state: MarkState,
+}
+
+/// ### 选项框识别器
+///
+/// 在已纠偏的答题卡图像中查找选项框，并测量每个选项框的填涂程度
+#[derive(Clone, Debug)]
+pub struct MarkReader {
+    /// 选项框的最小边长
+    pub min_size: i32,
+    /// 选项框的最大边长
+    pub max_size: i32,
+    /// 选项框长边与短边之比的最大值
+    pub max_aspect_ratio: f64,
+    /// 轮廓面积与其外接矩形面积之比的最小值，用于排除文字笔画、斜线等细长轮廓。
+    /// 圆形选项框约为 0.785，方形选项框约为 1
+    pub min_extent: f64,
+    /// 测量填涂程度时，选项框每侧向内收缩的比例，用于排除印刷边框，取值范围为 [0, 0.5)
+    pub inner_margin_ratio: f64,
+    /// 填涂比例不小于该值时认为已填涂
+    pub filled_threshold: f64,
+    /// 填涂比例不大于该值时认为未填涂
+    pub empty_threshold: f64,
+}
+impl Default for MarkReader {
+    fn default() -> Self {
+        Self {
+            min_size: 12,
+            max_size: 60,
+            max_aspect_ratio: 1.6,
+            min_extent: 0.6,
+            inner_margin_ratio: 0.2,
+            filled_threshold: 0.5,
+            empty_threshold: 0.25,
+        }
+    }
+}
+impl MarkReader {
+    /// ### 检查参数是否合法
+    ///
+    /// 参数不合法时返回 `Error::InvalidParameter`，包括：
+    /// - 选项框的最小边长不为正数，或大于最大边长
+    /// - 长宽比的最大值小于 1
+    /// - 轮廓面积比的最小值不在 [0, 1] 之间
+    /// - 向内收缩的比例不在 [0, 0.5) 之间，此时内部区域为空
+    /// - 填涂阈值不在 [0, 1] 之间，或未填涂阈值大于已填涂阈值
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |message: String| Err(Error::InvalidParameter(message));
+
+        if self.min_size <= 0 || self.min_size > self.max_size {
+            return invalid(format!(
+                "mark size range [{}, {}] is invalid",
+                self.min_size, self.max_size
+            ));
+        }
+        if self.max_aspect_ratio.is_nan() || self.max_aspect_ratio < 1.0 {
+            return invalid(format!(
+                "max aspect ratio must not be less than 1, got {}",
+                self.max_aspect_ratio
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.min_extent) {
+            return invalid(format!(
+                "min extent must be in [0, 1], got {}",
+                self.min_extent
+            ));
+        }
+        if !(0.0..0.5).contains(&self.inner_margin_ratio) {
+            return invalid(format!(
+                "inner margin ratio must be in [0, 0.5), got {}",
+                self.inner_margin_ratio
+            ));
+        }
+        if !((0.0..=1.0).contains(&self.empty_threshold)
+            && (0.0..=1.0).contains(&self.filled_threshold)
+            && self.empty_threshold <= self.filled_threshold)
+        {
+            return invalid(format!(
+                "thresholds must satisfy 0 <= empty ({}) <= filled ({}) <= 1",
+                self.empty_threshold, self.filled_threshold
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// 根据填涂比例判断填涂状态
+    pub fn get_state(&self, fill_ratio: f64) -> MarkState {
+        if fill_ratio >= self.filled_threshold {
+            MarkState::Filled
+        } else if fill_ratio <= self.empty_threshold {
+            MarkState::Empty
+        } else {
+            MarkState::Ambiguous
+        }
+    }
+}
+
+/// 模板中单个选项框的识别结果
+#[derive(Clone, Debug)]
+pub struct FieldMark {
+    /// 选项框的含义
+    pub field: BubbleField,
+    /// 选项框在图像中的外接矩形
+    pub rect: Rect,
+    /// 选项框内部的填涂比例，取值范围为 [0, 1]
+    pub fill_ratio: f64,
+    /// 填涂状态
+    pub state: MarkState,
+}
+
+/// 反相二值化，使墨迹成为前景
+fn get_ink_mat(src: &TransformableMatrix) -> Result<Mat> {
+    let gray_image = transfer::transfer_rgb_image_to_gray_image(src)?;
+    let mut dst = Mat::default();
+    imgproc::threshold(
+        gray_image.get_mat(),
+        &mut dst,
+        0.0,
+        255.0,
+        imgproc::THRESH_BINARY_INV | imgproc::THRESH_OTSU,
+    )?;
+
+    Ok(dst)
+}
+
+/// 计算选项框内部区域的填涂比例，排除印刷边框
+fn get_fill_ratio(ink_mat: &Mat, rect: Rect, reader: &MarkReader) -> Result<f64> {
+    let margin_x = (rect.width as f64 * reader.inner_margin_ratio) as i32;
+    let margin_y = (rect.height as f64 * reader.inner_margin_ratio) as i32;
+    let inner_rect = Rect::new(
+        rect.x + margin_x,
+        rect.y + margin_y,
+        (rect.width - margin_x * 2).max(1),
+        (rect.height - margin_y * 2).max(1),
+    );
+
+    let inner_mat = Mat::roi(ink_mat, inner_rect)?;
+    Ok(core::count_non_zero(&inner_mat)? as f64 / (inner_rect.width * inner_rect.height) as f64)
+}
+
+/// 将选项框按中心纵坐标分行，行内按横坐标排序
+fn get_mark_rows(mut rects: Vec<Rect>) -> Vec<Vec<Rect>> {
+    rects.sort_by_key(|rect| rect.y + rect.height / 2);
+
+    let mut rows: Vec<Vec<Rect>> = vec![];
+    for rect in rects {
+        let center_y = rect.y + rect.height / 2;
+        match rows.last_mut() {
+            // 中心纵坐标与上一行首个选项框相差不足半个框高时视为同一行
+            Some(row) if (center_y - (row[0].y + row[0].height / 2)).abs() < row[0].height / 2 => {
+                row.push(rect)
+            }
+            _ => rows.push(vec![rect]),
+        }
+    }
+
+    for row in rows.iter_mut() {
+        row.sort_by_key(|rect| rect.x);
+    }
+
+    rows
+}
+
+/// ### 读取答题卡中的选项框
+///
+/// **参数列表：**
+/// - `src`: 经 `omr::correct_default` 纠偏后的三通道图像
+/// - `reader`: 选项框识别器参数
+///
+/// 返回按行、列顺序排列的选项框识别结果，`reader` 不合法时返回 `Error::InvalidParameter`
+///
+/// 用例
+/// ```rust,no_run
+/// # use oics::omr::marks::{self, MarkReader, MarkState};
+/// # use oics::transfer::TransformableMatrix;
+/// # use opencv::imgcodecs;
+///
+/// let src = TransformableMatrix::new("01234.jpg", imgcodecs::IMREAD_COLOR).unwrap();
+/// let marks = marks::read_marks(&src, &MarkReader::default()).unwrap();
+/// for mark in marks.iter().filter(|mark| mark.state == MarkState::Filled) {
+///     println!("row {} column {}", mark.row, mark.column);
+/// }
+/// ```
+pub fn read_marks(src: &TransformableMatrix, reader: &MarkReader) -> Result<Vec<Mark>> {
+    reader.validate()?;
+    let thresh_mat = get_ink_mat(src)?;
+
+    let mut contours = VectorOfVectorOfPoint::new();
+    imgproc::find_contours(
+        &thresh_mat,
+        &mut contours,
+        imgproc::RETR_EXTERNAL,
+        imgproc::CHAIN_APPROX_SIMPLE,
+        core::Point::new(0, 0),
+    )?;
+
+    // 按尺寸、长宽比与轮廓面积比筛选选项框
+    let mut rects = vec![];
+    for contour in contours.iter() {
+        let rect = imgproc::bounding_rect(&contour)?;
+        let long_side = rect.width.max(rect.height);
+        let short_side = rect.width.min(rect.height);
+        if short_side < reader.min_size || long_side > reader.max_size {
+            continue;
+        }
+        if long_side as f64 / short_side as f64 > reader.max_aspect_ratio {
+            continue;
+        }
+        let extent = imgproc::contour_area(&contour, false)? / (rect.width * rect.height) as f64;
+        if extent < reader.min_extent {
+            continue;
+        }
+        rects.push(rect);
+    }
+
+    let mut marks = vec![];
+    for (row_index, row) in get_mark_rows(rects).into_iter().enumerate() {
+        for (column_index, rect) in row.into_iter().enumerate() {
+            let fill_ratio = get_fill_ratio(&thresh_mat, rect, reader)?;
+            marks.push(Mark {
+                row: row_index,
+                column: column_index,
+                rect,
+                fill_ratio,
+                state: reader.get_state(fill_ratio),
+            });
+        }
+    }
+
+    Ok(marks)
+}
+
+/// ### 按答题卡模板读取选项框
+///
+/// **参数列表：**
+/// - `src`: 经纠偏后的三通道图像，图像范围应与模板描述的答题卡范围一致
+/// - `template`: 答题卡模板
+/// - `reader`: 选项框识别器参数，仅使用其中的填涂判断参数，不合法时返回 `Error::InvalidParameter`
+///
+/// 与 `read_marks` 不同，选项框的位置由模板给出，不在图像中查找
+pub fn read_template_marks(
+    src: &TransformableMatrix,
+    template: &SheetTemplate,
+    reader: &MarkReader,
+) -> Result<Vec<FieldMark>> {
+    reader.validate()?;
+    let ink_mat = get_ink_mat(src)?;
+
+    let mut marks = vec![];
+    for region in template.project_onto(src)? {
+        let fill_ratio = if region.rect.width > 0 && region.rect.height > 0 {
+            get_fill_ratio(&ink_mat, region.rect, reader)?
+        } else {
+            0.0
+        };
+
+        marks.push(FieldMark {
+            field: region.field,
+            rect: region.rect,
+            fill_ratio,
+            state: reader.get_state(fill_ratio),
+        });
+    }
+
+    Ok(marks)
+}
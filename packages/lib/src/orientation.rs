@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    calculate,
+    error::{self, Result},
+    transfer::{self, TransformableMatrix},
+};
+
+/// 页面边缘，按顺时针顺序排列
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum PageEdge {
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+impl PageEdge {
+    fn index(&self) -> i32 {
+        match self {
+            Self::Top => 0,
+            Self::Right => 1,
+            Self::Bottom => 2,
+            Self::Left => 3,
+        }
+    }
+}
+
+/// 页面朝向，即页面摆正所需的逆时针旋转角度
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Orientation {
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+impl Orientation {
+    /// 由逆时针旋转的 1/4 圈数得到页面朝向
+    pub fn from_quarter_turns(quarter_turns: i32) -> Self {
+        match quarter_turns.rem_euclid(4) {
+            0 => Self::Deg0,
+            1 => Self::Deg90,
+            2 => Self::Deg180,
+            _ => Self::Deg270,
+        }
+    }
+
+    /// 页面摆正所需的逆时针旋转角度（deg）
+    pub fn angle(&self) -> f64 {
+        match self {
+            Self::Deg0 => 0.0,
+            Self::Deg90 => 90.0,
+            Self::Deg180 => 180.0,
+            Self::Deg270 => 270.0,
+        }
+    }
+}
+
+/// ### 页面朝向检测器
+///
+/// 利用答题卡版面的不对称性判断页面朝向：
+/// 先比较水平、垂直投影的离散程度判断文字行与题目行的方向，
+/// 再比较对应两侧边缘带内的墨迹密度，墨迹较重的一侧即为表头、定位标记所在一侧。
+///
+/// 输入图像应当已经完成偏转角纠正
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct OrientationDetector {
+    /// 版面中墨迹较重的一侧，即表头、定位标记所在的一侧
+    pub heavy_edge: PageEdge,
+    /// 边缘带宽度占图像尺寸的比例
+    pub band_ratio: f64,
+    /// 检测图像的最大宽度
+    pub max_width: i32,
+    /// 检测图像的最大高度
+    pub max_height: i32,
+}
+impl Default for OrientationDetector {
+    fn default() -> Self {
+        Self {
+            heavy_edge: PageEdge::Top,
+            band_ratio: 0.15,
+            max_width: 512,
+            max_height: 512,
+        }
+    }
+}
+
+/// 页面朝向检测结果
+#[derive(Clone, Debug)]
+pub struct OrientationResult {
+    /// 页面朝向
+    pub orientation: Orientation,
+    /// 置信度，取值范围为 [0, 1]
+    pub confidence: f64,
+}
+
+/// 计算投影数据中指定区间内的黑色像素密度
+fn get_band_density(projection: &[f64], start: usize, end: usize, length: usize) -> f64 {
+    let area = ((end - start) * length).max(1);
+    projection[start..end].iter().sum::<f64>() / area as f64
+}
+
+/// ### 检测已纠偏图像的页面朝向
+///
+/// **参数列表：**
+/// - `src`: 已完成偏转角纠正的三通道图像
+/// - `detector`: 页面朝向检测器参数
+///
+pub fn get_orientation(
+    src: &TransformableMatrix,
+    detector: &OrientationDetector,
+) -> Result<OrientationResult> {
+    error::check_not_empty(src.get_mat())?;
+
+    // 二值化图像
+    let thresh_image = {
+        let mut gray_image = transfer::transfer_rgb_image_to_gray_image(src)?;
+        gray_image.shrink_to(detector.max_width, detector.max_height)?;
+        transfer::transfer_gray_image_to_thresh_binary(&gray_image)?
+    };
+
+    // 水平投影为每一行的黑色像素数，垂直投影为每一列的黑色像素数
+    let horizontal_projection = transfer::get_horizontal_projection(&thresh_image)?;
+    let vertical_projection = transfer::get_vertical_projection(&thresh_image)?;
+    let (height, width) = (horizontal_projection.len(), vertical_projection.len());
+
+    let horizontal_mean = calculate::get_arithmetic_mean(&horizontal_projection)?;
+    let vertical_mean = calculate::get_arithmetic_mean(&vertical_projection)?;
+    if horizontal_mean <= 0.0 || vertical_mean <= 0.0 {
+        // 空白页面无法判断朝向
+        return Ok(OrientationResult {
+            orientation: Orientation::Deg0,
+            confidence: 0.0,
+        });
+    }
+
+    // 以变异系数比较两个方向投影的离散程度，文字行、题目行方向上的投影起伏更大
+    let horizontal_variation =
+        calculate::get_standard_deviation(&horizontal_projection)? / horizontal_mean;
+    let vertical_variation =
+        calculate::get_standard_deviation(&vertical_projection)? / vertical_mean;
+
+    let band_height = ((height as f64 * detector.band_ratio) as usize)
+        .max(1)
+        .min(height);
+    let band_width = ((width as f64 * detector.band_ratio) as usize)
+        .max(1)
+        .min(width);
+
+    let (observed_edge, heavy_density, light_density) = if horizontal_variation
+        >= vertical_variation
+    {
+        // 行方向水平，表头位于上侧或下侧
+        let top = get_band_density(&horizontal_projection, 0, band_height, width);
+        let bottom = get_band_density(&horizontal_projection, height - band_height, height, width);
+        if top >= bottom {
+            (PageEdge::Top, top, bottom)
+        } else {
+            (PageEdge::Bottom, bottom, top)
+        }
+    } else {
+        // 行方向垂直，表头位于左侧或右侧
+        let left = get_band_density(&vertical_projection, 0, band_width, height);
+        let right = get_band_density(&vertical_projection, width - band_width, width, height);
+        if left >= right {
+            (PageEdge::Left, left, right)
+        } else {
+            (PageEdge::Right, right, left)
+        }
+    };
+
+    // 行方向与墨迹分布两项判断均明确时置信度较高
+    let line_confidence = (horizontal_variation - vertical_variation).abs()
+        / horizontal_variation.max(vertical_variation);
+    let edge_confidence = if heavy_density + light_density > 0.0 {
+        (heavy_density - light_density) / (heavy_density + light_density)
+    } else {
+        0.0
+    };
+
+    Ok(OrientationResult {
+        orientation: Orientation::from_quarter_turns(
+            observed_edge.index() - detector.heavy_edge.index(),
+        ),
+        confidence: (line_confidence * edge_confidence).max(0.0).min(1.0),
+    })
+}
@@ -0,0 +1,228 @@
+use opencv::{
+    core::{Mat, Size2i},
+    imgproc,
+    prelude::MatTraitConst,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::Result,
+    transfer::{self, TransformableMatrix},
+    types::{BinarizationStrategy, DenoiseStrategy, IlluminationStrategy},
+};
+
+/// 预处理步骤
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum PreprocessStage {
+    /// 转换为灰度图，输入已是单通道图像时不做处理
+    Gray,
+    /// 光照归一化
+    Illumination { strategy: IlluminationStrategy },
+    /// 降噪
+    Denoise { strategy: DenoiseStrategy },
+    /// 以椭圆核进行腐蚀
+    Erode { kernel_size: i32, iterations: i32 },
+    /// 以椭圆核进行膨胀
+    Dilate { kernel_size: i32, iterations: i32 },
+    /// 按比例缩放
+    Scale { scale: f64 },
+    /// 等比缩小至不超过指定尺寸
+    ShrinkTo { max_width: i32, max_height: i32 },
+    /// 二值化，输入为三通道图像时先转换为灰度图
+    Threshold { strategy: BinarizationStrategy },
+}
+impl PreprocessStage {
+    /// 对图像应用该预处理步骤
+    pub fn apply(&self, src: &mut TransformableMatrix) -> Result<()> {
+        let get_kernel_size = |kernel_size: i32| Size2i::new(kernel_size, kernel_size);
+
+        match self {
+            Self::Gray => {
+                if src.get_mat().channels() != 1 {
+                    *src = transfer::transfer_rgb_image_to_gray_image(src)?;
+                }
+            }
+            Self::Illumination { strategy } => {
+                src.normalize_illumination(*strategy)?;
+            }
+            Self::Denoise { strategy } => {
+                src.denoise(*strategy)?;
+            }
+            Self::Erode {
+                kernel_size,
+                iterations,
+            } => {
+                *src = src.erode(
+                    imgproc::MORPH_ELLIPSE,
+                    get_kernel_size(*kernel_size),
+                    opencv::core::Point::new(-1, -1),
+                    *iterations,
+                )?;
+            }
+            Self::Dilate {
+                kernel_size,
+                iterations,
+            } => {
+                *src = src.dilate(
+                    imgproc::MORPH_ELLIPSE,
+                    get_kernel_size(*kernel_size),
+                    opencv::core::Point::new(-1, -1),
+                    *iterations,
+                )?;
+            }
+            Self::Scale { scale } => {
+                src.scale_self(*scale)?;
+            }
+            Self::ShrinkTo {
+                max_width,
+                max_height,
+            } => {
+                src.shrink_to(*max_width, *max_height)?;
+            }
+            Self::Threshold { strategy } => {
+                if src.get_mat().channels() != 1 {
+                    *src = transfer::transfer_rgb_image_to_gray_image(src)?;
+                }
+                *src = transfer::transfer_gray_image_to_thresh_binary_by_strategy(src, *strategy)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// ### 预处理流水线
+///
+/// 按顺序对图像应用一系列预处理步骤，可序列化为 JSON 以便针对不同扫描仪调整参数
+///
+/// 用例
+/// ```rust,no_run
+/// # use oics::pipeline::PreprocessPipeline;
+/// # use oics::transfer::TransformableMatrix;
+/// # use oics::types::BinarizationStrategy;
+/// # use opencv::imgcodecs;
+///
+/// let pipeline = PreprocessPipeline::new()
+///     .gray()
+///     .erode(3, 3)
+///     .shrink_to(248, 230)
+///     .threshold(BinarizationStrategy::OTSU);
+///
+/// let src = TransformableMatrix::new("01234.jpg", imgcodecs::IMREAD_COLOR).unwrap();
+/// let thresh_image = pipeline.apply(&src).unwrap();
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct PreprocessPipeline {
+    /// 预处理步骤
+    pub stages: Vec<PreprocessStage>,
+}
+impl PreprocessPipeline {
+    /// 生成空流水线
+    pub fn new() -> Self {
+        Self { stages: vec![] }
+    }
+
+    /// 追加预处理步骤
+    pub fn stage(mut self, stage: PreprocessStage) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    /// 追加灰度化步骤
+    pub fn gray(self) -> Self {
+        self.stage(PreprocessStage::Gray)
+    }
+
+    /// 追加光照归一化步骤
+    pub fn illumination(self, strategy: IlluminationStrategy) -> Self {
+        self.stage(PreprocessStage::Illumination { strategy })
+    }
+
+    /// 追加降噪步骤
+    pub fn denoise(self, strategy: DenoiseStrategy) -> Self {
+        self.stage(PreprocessStage::Denoise { strategy })
+    }
+
+    /// 追加腐蚀步骤
+    pub fn erode(self, kernel_size: i32, iterations: i32) -> Self {
+        self.stage(PreprocessStage::Erode {
+            kernel_size,
+            iterations,
+        })
+    }
+
+    /// 追加膨胀步骤
+    pub fn dilate(self, kernel_size: i32, iterations: i32) -> Self {
+        self.stage(PreprocessStage::Dilate {
+            kernel_size,
+            iterations,
+        })
+    }
+
+    /// 追加缩放步骤
+    pub fn scale(self, scale: f64) -> Self {
+        self.stage(PreprocessStage::Scale { scale })
+    }
+
+    /// 追加等比缩小步骤
+    pub fn shrink_to(self, max_width: i32, max_height: i32) -> Self {
+        self.stage(PreprocessStage::ShrinkTo {
+            max_width,
+            max_height,
+        })
+    }
+
+    /// 追加二值化步骤
+    pub fn threshold(self, strategy: BinarizationStrategy) -> Self {
+        self.stage(PreprocessStage::Threshold { strategy })
+    }
+
+    /// ### 与投影标准差方法内置预处理相近的流程
+    ///
+    /// 灰度化、3×3 椭圆核腐蚀 3 次、缩小、固定阈值二值化。
+    ///
+    /// 与 `omr::get_result_from_projection` 内置的预处理并不完全等价：
+    /// 内置流程总是按比例缩放至最大尺寸，小于最大尺寸的图像会被放大，
+    /// 且旋转二值图时再次以该缩放比例缩放；流水线仅缩小图像，旋转时不缩放。
+    /// 因此只有图像尺寸恰为最大尺寸，即缩放比例为 1 时，两者的结果一致
+    pub fn projection_default(max_width: i32, max_height: i32) -> Self {
+        Self::new()
+            .gray()
+            .erode(3, 3)
+            .shrink_to(max_width, max_height)
+            .threshold(BinarizationStrategy::default())
+    }
+
+    /// 从 JSON 字符串解析流水线
+    pub fn from_json_str(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// 将流水线序列化为 JSON 字符串
+    pub fn to_json_string(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// 对图像依次应用各预处理步骤，返回处理后的新图像
+    pub fn apply(&self, src: &TransformableMatrix) -> Result<TransformableMatrix> {
+        let mut dst = src.clone();
+        for stage in self.stages.iter() {
+            stage.apply(&mut dst)?;
+        }
+
+        Ok(dst)
+    }
+
+    /// 对图像应用流水线，并确保输出为三通道图像，以便交由检测器处理
+    pub fn apply_for_detector(&self, src: &TransformableMatrix) -> Result<TransformableMatrix> {
+        let dst = self.apply(src)?;
+        if dst.get_mat().channels() != 1 {
+            return Ok(dst);
+        }
+
+        let mut color_mat = Mat::default();
+        imgproc::cvt_color(dst.get_mat(), &mut color_mat, imgproc::COLOR_GRAY2BGR, 0)?;
+        Ok(TransformableMatrix::from_matrix(&color_mat))
+    }
+}
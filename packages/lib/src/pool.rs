@@ -0,0 +1,141 @@
+use std::{
+    num::NonZeroUsize,
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Arc, Mutex, OnceLock},
+    thread::{self, JoinHandle},
+};
+
+use crate::error::{Error, Result};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// ### 可复用的工作线程池
+///
+/// 线程在创建时启动，之后反复从任务队列中取出任务执行，避免每次计算都重新创建线程。
+///
+/// 不要在线程池的任务中调用同一线程池的 `map`，否则所有工作线程都可能在等待中阻塞
+///
+/// 用例
+/// ```rust
+/// # use oics::pool::WorkerPool;
+///
+/// let pool = WorkerPool::new(4);
+/// let squares = pool.map((0..10).collect(), |value: i32| value * value).unwrap();
+/// assert_eq!(squares[3], 9);
+/// ```
+pub struct WorkerPool {
+    sender: Option<Mutex<mpsc::Sender<Job>>>,
+    workers: Vec<JoinHandle<()>>,
+}
+impl WorkerPool {
+    /// 创建包含 `size` 个工作线程的线程池，`size` 为 0 时按 1 处理
+    pub fn new(size: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let workers = (0..size.max(1))
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                thread::spawn(move || loop {
+                    // 取出任务后立即释放锁，使其他线程可以同时取出任务
+                    let job = receiver.lock().unwrap().recv();
+                    match job {
+                        // 任务发生 panic 时保持线程存活，由调用方处理缺失的结果
+                        Ok(job) => {
+                            let _ = panic::catch_unwind(AssertUnwindSafe(job));
+                        }
+                        // 线程池已被销毁
+                        Err(_) => break,
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            sender: Some(Mutex::new(sender)),
+            workers,
+        }
+    }
+
+    /// 全局共享的线程池，线程数为可用的并行度
+    pub fn global() -> &'static WorkerPool {
+        static GLOBAL_POOL: OnceLock<WorkerPool> = OnceLock::new();
+        GLOBAL_POOL.get_or_init(|| {
+            WorkerPool::new(
+                thread::available_parallelism()
+                    .map(NonZeroUsize::get)
+                    .unwrap_or(1),
+            )
+        })
+    }
+
+    /// 工作线程数
+    pub fn size(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// 提交任务，任务将在空闲的工作线程中执行
+    ///
+    /// 线程池已停止运行时返回 `Error::WorkerPool`
+    pub fn execute<F>(&self, f: F) -> Result<()>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let get_stopped_error = || Error::WorkerPool(String::from("worker pool has stopped"));
+        let sender = self.sender.as_ref().ok_or_else(get_stopped_error)?;
+
+        sender
+            .lock()
+            .map_err(|_| get_stopped_error())?
+            .send(Box::new(f))
+            .map_err(|_| get_stopped_error())
+    }
+
+    /// ### 并行地对每个输入调用 `f`
+    ///
+    /// 返回结果的顺序与输入顺序一致，与任务的完成顺序及线程数无关。
+    /// 线程池已停止运行，或任一任务发生 panic 时返回 `Error::WorkerPool`
+    pub fn map<T, R, F>(&self, inputs: Vec<T>, f: F) -> Result<Vec<R>>
+    where
+        T: Send + 'static,
+        R: Send + 'static,
+        F: Fn(T) -> R + Send + Sync + 'static,
+    {
+        let f = Arc::new(f);
+        let (result_sender, result_receiver) = mpsc::channel();
+        let count = inputs.len();
+
+        for (index, input) in inputs.into_iter().enumerate() {
+            let f = Arc::clone(&f);
+            let result_sender = result_sender.clone();
+            self.execute(move || {
+                let _ = result_sender.send((index, f(input)));
+            })?;
+        }
+        // 所有任务结束后结果通道随之关闭
+        drop(result_sender);
+
+        let mut results: Vec<Option<R>> = (0..count).map(|_| None).collect();
+        for (index, result) in result_receiver {
+            results[index] = Some(result);
+        }
+
+        // 发生 panic 的任务没有结果
+        results
+            .into_iter()
+            .enumerate()
+            .map(|(index, result)| {
+                result.ok_or_else(|| Error::WorkerPool(format!("task {} panicked", index)))
+            })
+            .collect()
+    }
+}
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        // 关闭任务通道，工作线程在取完剩余任务后退出
+        drop(self.sender.take());
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
@@ -1,194 +1,276 @@
+pub mod sparse;
+
 use std::{
-    collections::HashMap,
-    sync::{Arc, Mutex},
-    thread,
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
 };
 
-use opencv::{core::Scalar, imgproc};
-
 use crate::{
+    calculate,
+    detector::ProjectionSweepDetector,
+    error::{self, Error, Result},
+    omr::{OmrResult, ResultStatus},
+    pool::WorkerPool,
+    projection::sparse::SparseBinaryImage,
     transfer::{
-        get_projection_standard_deviations, rotate_mat, transfer_gray_image_to_thresh_binary,
-        transfer_rgb_image_to_gray_image, TransformableMatrix,
+        transfer_gray_image_to_thresh_binary_by_strategy, transfer_rgb_image_to_gray_image,
+        TransformableMatrix,
     },
-    types::RotateClipStrategy,
+    types::PeakInterpolation,
 };
 
-pub fn get_angle_with_projections(
-    src_img: &TransformableMatrix,
+/// ### 并行计算各角度下的投影标准差
+///
+/// **参数列表：**
+/// - `sparse_image`: 稀疏二值图
+/// - `angles`: 旋转角度（deg）
+/// - `scale`: 旋转时的缩放比例
+/// - `threads`: 并行度，不大于 1 时在当前线程中计算
+///
+/// 角度被划分为 `threads` 个连续的分段，交由全局线程池 `WorkerPool::global` 计算。
+/// 每个角度的计算互相独立，返回结果的顺序与 `angles` 一致，与线程数无关。
+///
+/// 返回各角度的垂直投影标准差与水平投影标准差
+pub fn get_projection_standard_deviations_sweep(
+    sparse_image: &Arc<SparseBinaryImage>,
+    angles: &[f64],
+    scale: f64,
+    threads: usize,
+) -> Result<(Vec<f64>, Vec<f64>)> {
+    let standard_deviations: Vec<(f64, f64)> = if threads <= 1 || angles.len() <= 1 {
+        angles
+            .iter()
+            .map(|angle| sparse_image.get_projection_standard_deviations(*angle, scale))
+            .collect::<Result<_>>()?
+    } else {
+        let chunk_size = angles.len().div_ceil(threads);
+        let chunks: Vec<Vec<f64>> = angles
+            .chunks(chunk_size)
+            .map(|chunk| chunk.to_vec())
+            .collect();
+        let sparse_image = Arc::clone(sparse_image);
+
+        WorkerPool::global()
+            .map(chunks, move |chunk| {
+                chunk
+                    .iter()
+                    .map(|angle| sparse_image.get_projection_standard_deviations(*angle, scale))
+                    .collect::<Result<Vec<(f64, f64)>>>()
+            })?
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect()
+    };
+
+    Ok(standard_deviations.into_iter().unzip())
+}
+
+/// ### 从垂直、水平投影标准差中找出最有可能的角度序号
+///
+/// 两条曲线的最大值位于同一角度时直接取该角度，
+/// 否则在两条曲线的最大值所在角度中取两者平方和最大者，平方和相同时取序号较小者。
+/// 曲线为空或长度不一致时返回 `None`
+pub fn get_most_possible_index(vertical_vec: &[f64], horizontal_vec: &[f64]) -> Option<usize> {
+    if vertical_vec.is_empty() || vertical_vec.len() != horizontal_vec.len() {
+        return None;
+    }
+
+    let get_possibles = |vec: &[f64]| {
+        let mut possibles: (f64, Vec<usize>) = (vec[0], vec![0]); // (投影标准差值, 等于该值的角度)
+        for (index, val) in vec.iter().enumerate().skip(1) {
+            if *val > possibles.0 {
+                possibles.0 = *val;
+                possibles.1 = vec![index];
+            } else if *val == possibles.0 {
+                possibles.1.push(index);
+            }
+        }
+        possibles
+    };
+    let vertical_possibles = get_possibles(vertical_vec);
+    let horizontal_possibles = get_possibles(horizontal_vec);
+
+    // 唯一结果且相等
+    if vertical_possibles.1.len() == 1
+        && horizontal_possibles.1.len() == 1
+        && vertical_possibles.1[0] == horizontal_possibles.1[0]
+    {
+        return Some(vertical_possibles.1[0]);
+    }
+
+    // 以有序表保存候选角度，使平方和相同时的选择不受遍历顺序影响
+    let mut candidate_map = BTreeMap::new();
+    for deg_index in vertical_possibles
+        .1
+        .into_iter()
+        .chain(horizontal_possibles.1.into_iter())
+    {
+        candidate_map
+            .entry(deg_index)
+            .or_insert((vertical_vec[deg_index], horizontal_vec[deg_index]));
+    }
+
+    let mut sdp = 0.0;
+    let mut most_possible_angle: Option<usize> = None;
+    for (angle, (vsd, hsd)) in candidate_map {
+        let cur_sdp = vsd.powf(2.0) + hsd.powf(2.0);
+        if sdp < cur_sdp {
+            sdp = cur_sdp;
+            most_possible_angle = Some(angle);
+        }
+    }
+
+    Some(most_possible_angle.unwrap_or(vertical_vec.len() / 2))
+}
+
+/// 曲线中所有等于最大值的序号
+fn get_max_indexes(vec: &[f64]) -> Vec<usize> {
+    let max = vec.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    vec.iter()
+        .enumerate()
+        .filter(|(_, val)| **val == max)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// ### 在二值图上扫描各角度的投影标准差，得出偏转角
+///
+/// **参数列表：**
+/// - `thresh_image`: 黑白二值图
+/// - `max_angle`: 最大偏转角度
+/// - `step`: 角度步长
+/// - `threads`: 并行度
+/// - `peak_interpolation`: 峰值插值方式
+///
+/// 垂直、水平投影标准差的最大值位于同一角度且唯一时结果为 `Believed`，否则为 `NeedCheck`。
+/// 置信度与估计误差由两者平方和曲线的峰值计算。
+///
+/// 结果与 `threads` 无关。`step` 不为正数、大于 `max_angle`，
+/// 或 `max_angle` 与 `step` 之比超出 `u16` 的范围时返回 `Error::InvalidParameter`
+pub fn get_result_from_thresh_binary(
+    thresh_image: &TransformableMatrix,
+    max_angle: u16,
+    step: f64,
+    threads: usize,
+    peak_interpolation: PeakInterpolation,
+) -> Result<OmrResult> {
+    let max_angle = error::check_angle_range("projection", max_angle, step)?;
+    let min_angle = -(max_angle as i32);
+    let angles: Vec<f64> = (min_angle..(max_angle as i32))
+        .map(|deg| deg as f64 * step)
+        .collect();
+    if angles.is_empty() {
+        return Err(Error::InvalidParameter(format!(
+            "angle step {} is larger than the max angle",
+            step
+        )));
+    }
+
+    // 以稀疏二值图代替逐个角度旋转整幅二值图，投影结果一致
+    let sparse_image = Arc::new(SparseBinaryImage::from_mat(thresh_image.get_mat())?);
+    let (vertical_vec, horizontal_vec) =
+        get_projection_standard_deviations_sweep(&sparse_image, &angles, 1.0, threads)?;
+
+    // 获取最有可能的角度，角度非空时必然存在
+    let most_possible_index =
+        get_most_possible_index(&vertical_vec, &horizontal_vec).unwrap_or(angles.len() / 2);
+
+    // 插值、置信度与估计误差均基于垂直、水平投影标准差的平方和曲线
+    let combined_vec: Vec<f64> = vertical_vec
+        .iter()
+        .zip(horizontal_vec.iter())
+        .map(|(vsd, hsd)| vsd.powf(2.0) + hsd.powf(2.0))
+        .collect();
+
+    // 通过峰值插值获取小于步长的角度偏移量
+    let offset = calculate::get_peak_offset(&combined_vec, most_possible_index, peak_interpolation);
+    let angle = (most_possible_index as f64 + offset - (max_angle as f64)) * step;
+
+    let vertical_indexes = get_max_indexes(&vertical_vec);
+    let horizontal_indexes = get_max_indexes(&horizontal_vec);
+    let status = if vertical_indexes[..] == [most_possible_index]
+        && horizontal_indexes[..] == [most_possible_index]
+    {
+        ResultStatus::Believed
+    } else {
+        ResultStatus::NeedCheck
+    };
+
+    // 候选角度为两条曲线的所有最大值所在角度
+    let candidates = match status {
+        ResultStatus::Believed => vec![angle],
+        _ => vertical_indexes
+            .into_iter()
+            .chain(horizontal_indexes)
+            .collect::<BTreeSet<usize>>()
+            .into_iter()
+            .map(|index| angles[index])
+            .collect(),
+    };
+
+    Ok(OmrResult {
+        angle,
+        status,
+        candidates,
+        confidence: calculate::get_peak_confidence(&combined_vec, most_possible_index),
+        uncertainty: calculate::get_peak_half_width(&combined_vec, most_possible_index) * step,
+    })
+}
+
+/// ### 在二值图上扫描各角度的投影标准差，得出偏转角
+///
+/// 仅返回 `get_result_from_thresh_binary` 结果中的角度
+pub fn get_angle_from_thresh_binary(
+    thresh_image: &TransformableMatrix,
     max_angle: u16,
     step: f64,
-    resize_scale: f64,
     threads: usize,
-) -> f64 {
+    peak_interpolation: PeakInterpolation,
+) -> Result<f64> {
+    get_result_from_thresh_binary(thresh_image, max_angle, step, threads, peak_interpolation)
+        .map(|result| result.angle)
+}
+
+/// ### 缩放、二值化三通道图像后扫描各角度的投影标准差，得出偏转角
+///
+/// **参数列表：**
+/// - `src_img`: 三通道图像
+/// - `detector`: 投影标准差扫描检测器参数
+///
+pub fn get_result_with_projections(
+    src_img: &TransformableMatrix,
+    detector: &ProjectionSweepDetector,
+) -> Result<OmrResult> {
+    error::check_not_empty(src_img.get_mat())?;
     let scaled_img = {
         let mut cloned_img = src_img.clone();
-        cloned_img.scale_self(resize_scale).unwrap().to_owned()
+        cloned_img
+            .scale_self(detector.resize_scale)?
+            .normalize_illumination(detector.illumination)?
+            .to_owned()
     };
     // 二值化图像
     let thresh_image = {
-        let gray_image = transfer_rgb_image_to_gray_image(&scaled_img).unwrap();
-        transfer_gray_image_to_thresh_binary(&gray_image).unwrap()
+        let gray_image = transfer_rgb_image_to_gray_image(&scaled_img)?;
+        transfer_gray_image_to_thresh_binary_by_strategy(&gray_image, detector.binarization)?
     };
 
     // 查找目标角度
-    let projection_angle = {
-        let max_angle = (max_angle as f64 / step) as u16;
-        let min_angle = -(max_angle as i32);
-        let range = min_angle..(max_angle as i32);
-
-        let standard_deviations = if threads <= 1 {
-            // 单线程
-            let mut standard_deviations = (
-                Vec::with_capacity(range.len()),
-                Vec::with_capacity(range.len()),
-            );
-
-            for deg in range {
-                let rotated_image = rotate_mat(
-                    &thresh_image,
-                    deg as f64 * step,
-                    1.0,
-                    imgproc::WARP_POLAR_LINEAR,
-                    opencv::core::BORDER_CONSTANT,
-                    Scalar::new(255.0, 255.0, 255.0, 0.0), // b g r
-                    RotateClipStrategy::DEFAULT,
-                )
-                .expect("旋转图像时发生错误！");
-
-                let projection_standard_deviations =
-                    get_projection_standard_deviations(&rotated_image)
-                        .expect("计算投影标准差时发生错误");
-
-                standard_deviations.0.push(projection_standard_deviations.0);
-                standard_deviations.1.push(projection_standard_deviations.1);
-            }
-
-            standard_deviations
-        } else {
-            // 多线程
-            let mut handles = Vec::with_capacity(threads);
-            let index = Arc::new(Mutex::new(0));
-
-            let arc_standard_deviations =
-                Arc::new(Mutex::new((vec![0.0; range.len()], vec![0.0; range.len()])));
-            let arc_thresh_image = Arc::new(thresh_image);
-            for _ in 0..threads {
-                let ref_standard_deviations = Arc::clone(&arc_standard_deviations);
-                let ref_index = Arc::clone(&index);
-                let ref_thresh_image = Arc::clone(&arc_thresh_image);
-
-                let handle = thread::spawn(move || loop {
-                    let angle = {
-                        let mut index_locker = ref_index.lock().unwrap();
-                        let current_index = *index_locker;
-                        if current_index == (max_angle * 2) as i32 {
-                            break;
-                        }
-                        *index_locker = current_index + 1;
-                        current_index + min_angle
-                    };
-
-                    let rotated_image = rotate_mat(
-                        &ref_thresh_image,
-                        angle as f64,
-                        1.0,
-                        imgproc::WARP_POLAR_LINEAR,
-                        opencv::core::BORDER_CONSTANT,
-                        Scalar::new(255.0, 255.0, 255.0, 0.0), // b g r
-                        RotateClipStrategy::DEFAULT,
-                    )
-                    .expect("旋转图像时发生错误！");
-
-                    let projection_standard_deviations =
-                        get_projection_standard_deviations(&rotated_image)
-                            .expect("计算投影标准差时发生错误");
-
-                    {
-                        let mut rsd = ref_standard_deviations.lock().unwrap();
-                        rsd.0[(angle - min_angle) as usize] = projection_standard_deviations.0;
-                        rsd.1[(angle - min_angle) as usize] = projection_standard_deviations.1;
-                    }
-                });
-                handles.push(handle);
-            }
-
-            for handle in handles {
-                handle.join().unwrap();
-            }
-
-            let sd = arc_standard_deviations.lock().unwrap().to_owned();
-            sd
-        };
-
-        // return
-        ((
-            // 获取最有可能的角度
-            {
-                // 处理垂直投影数据
-                let vertical_vec = standard_deviations.0;
-                let mut vertical_possibles: (f64, Vec<usize>) = (vertical_vec[0], vec![0]); // (投影标准差值, 等于该值的角度)
-                for (index, val) in vertical_vec.iter().enumerate() {
-                    if *val > vertical_possibles.0 {
-                        vertical_possibles.0 = *val;
-                        vertical_possibles.1 = vec![index];
-                    } else if *val == vertical_possibles.0 {
-                        vertical_possibles.1.push(index);
-                    }
-                }
-
-                // 处理水平投影数据
-                let horizontal_vec = standard_deviations.1;
-                let mut horizontal_possibles: (f64, Vec<usize>) = (horizontal_vec[0], vec![0]); // (投影标准差值, 等于该值的角度)
-                for (index, val) in horizontal_vec.iter().enumerate() {
-                    if *val > horizontal_possibles.0 {
-                        horizontal_possibles.0 = *val;
-                        horizontal_possibles.1 = vec![index];
-                    } else if *val == horizontal_possibles.0 {
-                        horizontal_possibles.1.push(index);
-                    }
-                }
-
-                // 唯一结果且相等
-                if vertical_possibles.1.len() == 1
-                    && horizontal_possibles.1.len() == 1
-                    && vertical_possibles.1[0] == horizontal_possibles.1[0]
-                {
-                    vertical_possibles.1[0]
-                } else {
-                    let mut candidate_hashmap = HashMap::new();
-                    for deg_index in vertical_possibles.1 {
-                        candidate_hashmap.entry(deg_index).or_insert((
-                            vertical_vec[deg_index as usize],
-                            horizontal_vec[deg_index as usize],
-                        ));
-                    }
-                    for deg_index in horizontal_possibles.1 {
-                        candidate_hashmap.entry(deg_index).or_insert((
-                            vertical_vec[deg_index as usize],
-                            horizontal_vec[deg_index as usize],
-                        ));
-                    }
-
-                    let mut sdp = 0.0;
-                    let mut most_possible_angle: Option<usize> = None;
-                    for (angle, (vsd, hsd)) in candidate_hashmap {
-                        let cur_sdp = vsd.powf(2.0) + hsd.powf(2.0);
-                        if sdp < cur_sdp {
-                            sdp = cur_sdp;
-                            most_possible_angle = Some(angle);
-                        }
-                    }
-
-                    match most_possible_angle {
-                        Some(value) => value,
-                        None => vertical_vec.len() / 2,
-                    }
-                }
-            } as f64
-        ) - (max_angle as f64))
-            * step
-    };
+    get_result_from_thresh_binary(
+        &thresh_image,
+        detector.max_angle,
+        detector.step,
+        detector.threads,
+        detector.peak_interpolation,
+    )
+}
 
-    return projection_angle;
+/// 仅返回 `get_result_with_projections` 结果中的角度
+pub fn get_angle_with_projections(
+    src_img: &TransformableMatrix,
+    detector: &ProjectionSweepDetector,
+) -> Result<f64> {
+    get_result_with_projections(src_img, detector).map(|result| result.angle)
 }
@@ -0,0 +1,220 @@
+use opencv::{
+    core::Mat,
+    prelude::{MatTraitConst, MatTraitConstManual},
+};
+
+use crate::{
+    calculate,
+    error::{self, Result},
+};
+
+/// `warp_affine` 计算映射坐标时使用的定点小数位数
+const AB_BITS: i32 = 10;
+const AB_SCALE: f64 = (1 << AB_BITS) as f64;
+/// 最近邻插值时的舍入偏移量
+const ROUND_DELTA: i32 = 1 << (AB_BITS - 1);
+
+/// 与 C++ `saturate_cast<int>(double)` 一致的舍入方式，即四舍六入五成双
+fn saturate_cast(value: f64) -> i32 {
+    value
+        .round_ties_even()
+        .clamp(i32::MIN as f64, i32::MAX as f64) as i32
+}
+
+/// ### 输出图像到输入图像的坐标映射
+///
+/// 复现 `imgproc::get_rotation_matrix_2d` 与 `imgproc::warp_affine` 在最近邻插值下的定点运算，
+/// 使映射结果与旋转图像逐像素一致
+struct NearestRotationMap {
+    /// 各输出列对应的定点横、纵坐标增量
+    column_deltas: Vec<(i32, i32)>,
+    /// 各输出行对应的定点横、纵坐标基准
+    row_bases: Vec<(i32, i32)>,
+    /// 正向变换矩阵，输入坐标到输出坐标
+    forward: [f64; 6],
+}
+impl NearestRotationMap {
+    fn new(width: i32, height: i32, angle: f64, scale: f64) -> Self {
+        // imgproc::get_rotation_matrix_2d，旋转中心为 f32 类型
+        let (center_x, center_y) = ((width as f32 / 2.0) as f64, (height as f32 / 2.0) as f64);
+        let radian = angle * std::f64::consts::PI / 180.0;
+        let alpha = radian.cos() * scale;
+        let beta = radian.sin() * scale;
+        let forward = [
+            alpha,
+            beta,
+            (1.0 - alpha) * center_x - beta * center_y,
+            -beta,
+            alpha,
+            beta * center_x + (1.0 - alpha) * center_y,
+        ];
+
+        // imgproc::warp_affine 内部对变换矩阵求逆
+        let mut inverse = forward;
+        let determinant = inverse[0] * inverse[4] - inverse[1] * inverse[3];
+        let determinant = if determinant != 0.0 {
+            1.0 / determinant
+        } else {
+            0.0
+        };
+        let (a11, a22) = (inverse[4] * determinant, inverse[0] * determinant);
+        inverse[0] = a11;
+        inverse[1] *= -determinant;
+        inverse[3] *= -determinant;
+        inverse[4] = a22;
+        let b1 = -inverse[0] * inverse[2] - inverse[1] * inverse[5];
+        let b2 = -inverse[3] * inverse[2] - inverse[4] * inverse[5];
+        inverse[2] = b1;
+        inverse[5] = b2;
+
+        Self {
+            column_deltas: (0..width)
+                .map(|x| {
+                    (
+                        saturate_cast(inverse[0] * x as f64 * AB_SCALE),
+                        saturate_cast(inverse[3] * x as f64 * AB_SCALE),
+                    )
+                })
+                .collect(),
+            row_bases: (0..height)
+                .map(|y| {
+                    (
+                        saturate_cast((inverse[1] * y as f64 + inverse[2]) * AB_SCALE)
+                            + ROUND_DELTA,
+                        saturate_cast((inverse[4] * y as f64 + inverse[5]) * AB_SCALE)
+                            + ROUND_DELTA,
+                    )
+                })
+                .collect(),
+            forward,
+        }
+    }
+
+    /// 输出像素 (`x`, `y`) 所取的输入像素坐标
+    fn get_source(&self, x: usize, y: usize) -> (i32, i32) {
+        let (column_x, column_y) = self.column_deltas[x];
+        let (row_x, row_y) = self.row_bases[y];
+        ((row_x + column_x) >> AB_BITS, (row_y + column_y) >> AB_BITS)
+    }
+}
+
+/// ### 以黑色像素坐标表示的稀疏二值图
+///
+/// 投影标准差方法在每个角度下旋转整幅二值图再统计投影，其中绝大部分工作量消耗在白色像素上。
+/// 稀疏二值图只提取一次黑色像素坐标，在每个角度下将其映射至旋转后的行、列并直接累加投影，
+/// 计算量与黑色像素数目成正比，适用于黑色像素稀少的答题卡。
+///
+/// 映射方式与 `imgproc::warp_affine` 的最近邻插值一致，投影数据与旋转图像后统计的结果完全相同
+///
+/// 用例
+/// ```rust,no_run
+/// # use oics::projection::sparse::SparseBinaryImage;
+/// # use oics::transfer::{self, TransformableMatrix};
+/// # use opencv::imgcodecs;
+///
+/// let src = TransformableMatrix::new("01234.jpg", imgcodecs::IMREAD_GRAYSCALE).unwrap();
+/// let thresh_image = transfer::transfer_gray_image_to_thresh_binary(&src).unwrap();
+/// let sparse_image = SparseBinaryImage::from_mat(thresh_image.get_mat()).unwrap();
+/// for deg in -10..10 {
+///     println!("{:?}", sparse_image.get_projection_standard_deviations(deg as f64, 1.0).unwrap());
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct SparseBinaryImage {
+    width: i32,
+    height: i32,
+    /// 按行优先顺序排列的黑色像素坐标 (x, y)
+    points: Vec<(i32, i32)>,
+}
+impl SparseBinaryImage {
+    /// 从黑白二值图中提取值为 0 的像素，图像为空时返回 `Error::EmptyImage`
+    pub fn from_mat(thresh_binary_mat: &Mat) -> Result<Self> {
+        error::check_not_empty(thresh_binary_mat)?;
+
+        let mut points = vec![];
+        for row_index in 0..thresh_binary_mat.rows() {
+            let row = thresh_binary_mat.at_row::<u8>(row_index)?;
+            for (col_index, item) in row.iter().enumerate() {
+                if *item == 0 {
+                    points.push((col_index as i32, row_index));
+                }
+            }
+        }
+
+        Ok(Self {
+            width: thresh_binary_mat.cols(),
+            height: thresh_binary_mat.rows(),
+            points,
+        })
+    }
+
+    /// 黑色像素数目
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// 是否不含黑色像素
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// ### 计算以图像中心旋转指定角度后的投影数据
+    ///
+    /// **参数列表：**
+    /// - `angle`: 旋转角度（deg），与 `imgproc::get_rotation_matrix_2d` 一致
+    /// - `scale`: 旋转时的缩放比例
+    ///
+    /// 旋转后的图像尺寸与原图相同，超出范围的部分视为白色。
+    /// 返回横向投影（每行的黑色像素数）与纵向投影（每列的黑色像素数）
+    pub fn get_projections(&self, angle: f64, scale: f64) -> (Vec<f64>, Vec<f64>) {
+        let (width, height) = (self.width.max(0) as usize, self.height.max(0) as usize);
+        let mut horizontal_projection = vec![0.0; height];
+        let mut vertical_projection = vec![0.0; width];
+        if width == 0 || height == 0 {
+            return (horizontal_projection, vertical_projection);
+        }
+
+        let map = NearestRotationMap::new(self.width, self.height, angle, scale);
+        let forward = &map.forward;
+        // 输入像素覆盖的单位方格在输出图像中的半宽、半高，并为定点运算的误差留出余量
+        let radius_x = (forward[0].abs() + forward[1].abs()) / 2.0 + 4.0 / AB_SCALE;
+        let radius_y = (forward[3].abs() + forward[4].abs()) / 2.0 + 4.0 / AB_SCALE;
+
+        for (source_x, source_y) in self.points.iter() {
+            let (x, y) = (*source_x as f64, *source_y as f64);
+            let target_x = forward[0] * x + forward[1] * y + forward[2];
+            let target_y = forward[3] * x + forward[4] * y + forward[5];
+
+            // 仅检查可能映射至该输入像素的输出像素
+            let min_x = (target_x - radius_x).ceil().max(0.0);
+            let max_x = (target_x + radius_x).floor().min((width - 1) as f64);
+            let min_y = (target_y - radius_y).ceil().max(0.0);
+            let max_y = (target_y + radius_y).floor().min((height - 1) as f64);
+            if min_x > max_x || min_y > max_y {
+                continue;
+            }
+
+            for dst_y in min_y as usize..=max_y as usize {
+                for dst_x in min_x as usize..=max_x as usize {
+                    if map.get_source(dst_x, dst_y) == (*source_x, *source_y) {
+                        horizontal_projection[dst_y] += 1.0;
+                        vertical_projection[dst_x] += 1.0;
+                    }
+                }
+            }
+        }
+
+        (horizontal_projection, vertical_projection)
+    }
+
+    /// 计算旋转指定角度后的垂直投影标准差和水平投影标准差，
+    /// 与 `transfer::get_projection_standard_deviations` 的返回顺序一致
+    pub fn get_projection_standard_deviations(&self, angle: f64, scale: f64) -> Result<(f64, f64)> {
+        let (horizontal_projection, vertical_projection) = self.get_projections(angle, scale);
+
+        Ok((
+            calculate::get_standard_deviation(&vertical_projection)?,
+            calculate::get_standard_deviation(&horizontal_projection)?,
+        ))
+    }
+}
@@ -0,0 +1,134 @@
+use opencv::prelude::{MatTraitConst, MatTraitConstManual};
+
+use crate::{
+    calculate,
+    error::{self, Result},
+    omr::{OmrResult, ResultStatus},
+    transfer::{self, TransformableMatrix},
+    types::{BinarizationStrategy, PeakInterpolation},
+};
+
+/// ### 计算二值图的 Radon 变换正弦图
+///
+/// **参数列表：**
+/// - `thresh_mat`: 黑色（0）为墨迹的二值图
+/// - `angles`: 积分方向角（deg），与图像 x 轴的夹角，图像坐标系下 y 轴向下
+///
+/// 仅遍历一次图像，对每个墨迹像素累加其在各方向上的投影位置，
+/// 返回每个角度对应的投影数组，投影位置以像素为单位
+pub fn get_sinogram(thresh_mat: &opencv::core::Mat, angles: &[f64]) -> Result<Vec<Vec<f64>>> {
+    let (rows, cols) = (thresh_mat.rows(), thresh_mat.cols());
+    // 投影位置的取值范围为 [-diagonal, diagonal]
+    let diagonal = ((rows as f64).powf(2.0) + (cols as f64).powf(2.0))
+        .sqrt()
+        .ceil() as usize;
+    let bins_count = diagonal * 2 + 1;
+
+    let directions: Vec<(f64, f64)> = angles
+        .iter()
+        .map(|angle| {
+            let radian = angle.to_radians();
+            (radian.sin(), radian.cos())
+        })
+        .collect();
+
+    let mut sinogram = vec![vec![0.0; bins_count]; angles.len()];
+    for row_index in 0..rows {
+        let row = thresh_mat.at_row::<u8>(row_index)?;
+        for (col_index, pixel) in row.iter().enumerate() {
+            if *pixel != 0 {
+                continue;
+            }
+
+            // 直线 y = x * tan(θ) + c 上的点具有相同的 ρ = y * cos(θ) - x * sin(θ)
+            let (x, y) = (col_index as f64, row_index as f64);
+            for (projection, (sin, cos)) in sinogram.iter_mut().zip(directions.iter()) {
+                let rho = y * cos - x * sin;
+                projection[(rho.round() as i64 + diagonal as i64) as usize] += 1.0;
+            }
+        }
+    }
+
+    Ok(sinogram)
+}
+
+/// ### 利用 Radon 变换查找偏转角
+///
+/// 投影标准差方法的一般形式：对二值图在一组角度上计算线积分，
+/// 文字行、表格线与积分方向一致时投影起伏最大，取正弦图中方差最大的角度作为偏转角
+///
+/// **参数列表：**
+/// - `src`: 三通道图像
+/// - `max_angle`: 最大偏转角度
+/// - `angle_step`: 角度步长
+/// - `max_width`: 变换图像的最大宽度
+/// - `max_height`: 变换图像的最大高度
+/// - `binarization`: 二值化方式
+/// - `peak_interpolation`: 峰值插值方式
+///
+pub fn get_result_with_radon(
+    src: &TransformableMatrix,
+    max_angle: u16,
+    angle_step: f64,
+    max_width: i32,
+    max_height: i32,
+    binarization: BinarizationStrategy,
+    peak_interpolation: PeakInterpolation,
+) -> Result<OmrResult> {
+    error::check_not_empty(src.get_mat())?;
+    error::check_positive("angle step", angle_step)?;
+
+    // 二值化图像
+    let thresh_image = {
+        let mut gray_image = transfer::transfer_rgb_image_to_gray_image(src)?;
+        gray_image.shrink_to(max_width, max_height)?;
+        transfer::transfer_gray_image_to_thresh_binary_by_strategy(&gray_image, binarization)?
+    };
+
+    let range_max_index = (max_angle as f64 / angle_step) as i32;
+    let angles: Vec<f64> = (-range_max_index..=range_max_index)
+        .map(|index| index as f64 * angle_step)
+        .collect();
+
+    // 各角度投影的方差
+    let variances: Vec<f64> = get_sinogram(thresh_image.get_mat(), &angles)?
+        .iter()
+        .map(|projection| calculate::get_standard_deviation(projection).map(|sd| sd.powf(2.0)))
+        .collect::<Result<_>>()?;
+
+    let max_variance = variances.iter().cloned().fold(f64::MIN, f64::max);
+    let candidates: Vec<usize> = (0..variances.len())
+        .filter(|index| variances[*index] == max_variance)
+        .collect();
+
+    if candidates.is_empty() || candidates.len() == variances.len() {
+        // 各角度方差相同，如空白图像
+        return Ok(OmrResult {
+            angle: 0.0,
+            status: ResultStatus::NotAResult,
+            candidates: vec![],
+            confidence: 0.0,
+            uncertainty: max_angle as f64,
+        });
+    }
+
+    let most_possible_index = candidates[0];
+    let offset = if candidates.len() == 1 {
+        calculate::get_peak_offset(&variances, most_possible_index, peak_interpolation)
+    } else {
+        0.0
+    };
+
+    Ok(OmrResult {
+        angle: (most_possible_index as f64 + offset) * angle_step
+            - range_max_index as f64 * angle_step,
+        status: if candidates.len() == 1 {
+            ResultStatus::Believed
+        } else {
+            ResultStatus::NeedCheck
+        },
+        candidates: candidates.iter().map(|index| angles[*index]).collect(),
+        confidence: calculate::get_peak_confidence(&variances, most_possible_index),
+        uncertainty: calculate::get_peak_half_width(&variances, most_possible_index) * angle_step,
+    })
+}
@@ -0,0 +1,326 @@
+use std::{collections::BTreeSet, fs, io::Write, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Error, Result},
+    omr::marks::{FieldMark, MarkState},
+    template::BubbleField,
+};
+
+/// 多选题的部分得分方式
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PartialCredit {
+    /// 完全正确才得分
+    #[default]
+    None,
+    /// 未选错误选项时，按所选正确选项的比例得分
+    Proportional,
+}
+
+/// 单道题目的答案
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct QuestionKey {
+    /// 题号，与答题卡模板一致
+    pub question: u32,
+    /// 正确选项的序号，从 0 开始计数，包含多个选项时为多选题
+    pub correct_options: Vec<u32>,
+    /// 题目分值
+    #[serde(default = "QuestionKey::default_weight")]
+    pub weight: f64,
+    /// 部分得分方式
+    #[serde(default)]
+    pub partial_credit: PartialCredit,
+}
+impl QuestionKey {
+    fn default_weight() -> f64 {
+        1.0
+    }
+
+    /// 根据所选选项计算得分
+    pub fn get_score(&self, selected_options: &BTreeSet<u32>) -> f64 {
+        let correct_options: BTreeSet<u32> = self.correct_options.iter().copied().collect();
+        if *selected_options == correct_options {
+            return self.weight;
+        }
+
+        match self.partial_credit {
+            PartialCredit::None => 0.0,
+            PartialCredit::Proportional => {
+                if !selected_options.is_empty() && selected_options.is_subset(&correct_options) {
+                    self.weight * selected_options.len() as f64 / correct_options.len() as f64
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// ### 答案
+///
+/// 用例
+/// ```rust,no_run
+/// # use oics::omr::marks::{self, MarkReader};
+/// # use oics::score::{self, AnswerKey};
+/// # use oics::template::SheetTemplate;
+/// # use oics::transfer::TransformableMatrix;
+/// # use opencv::imgcodecs;
+///
+/// let template = SheetTemplate::load("exam.json").unwrap();
+/// let key = AnswerKey::load("answer.json").unwrap();
+/// let src = TransformableMatrix::new("01234.jpg", imgcodecs::IMREAD_COLOR).unwrap();
+/// let marks = marks::read_template_marks(&src, &template, &MarkReader::default()).unwrap();
+///
+/// let report = score::score_sheet(&marks, &key);
+/// let mut file = std::fs::File::create("result.csv").unwrap();
+/// score::write_csv(&mut file, &key, &[(String::from("01234.jpg"), report)]).unwrap();
+/// ```
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AnswerKey {
+    /// 各题答案
+    pub questions: Vec<QuestionKey>,
+}
+impl AnswerKey {
+    /// 从 JSON 字符串解析答案并校验
+    pub fn from_json_str(json: &str) -> Result<Self> {
+        let key: Self = serde_json::from_str(json)?;
+        key.validate()?;
+
+        Ok(key)
+    }
+
+    /// 从 JSON 文件加载答案并校验
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let json = fs::read_to_string(path)?;
+        Self::from_json_str(&json)
+    }
+
+    /// ### 校验答案
+    ///
+    /// 检查各题是否给出正确选项、分值是否为非负数、题号是否重复，
+    /// 不合法时返回 `Error::InvalidConfig`
+    pub fn validate(&self) -> Result<()> {
+        let mut questions = BTreeSet::new();
+        for question in self.questions.iter() {
+            // 没有正确选项时，未作答反而得满分
+            if question.correct_options.is_empty() {
+                return Err(Error::InvalidConfig(format!(
+                    "question {} has no correct options",
+                    question.question
+                )));
+            }
+            if !(question.weight.is_finite() && question.weight >= 0.0) {
+                return Err(Error::InvalidConfig(format!(
+                    "weight of question {} must be a non-negative number, got {}",
+                    question.question, question.weight
+                )));
+            }
+            if !questions.insert(question.question) {
+                return Err(Error::InvalidConfig(format!(
+                    "question {} is defined more than once",
+                    question.question
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 总分
+    pub fn get_max_score(&self) -> f64 {
+        self.questions.iter().map(|question| question.weight).sum()
+    }
+}
+
+/// 单道题目的得分
+#[derive(Clone, Debug, Serialize)]
+pub struct QuestionScore {
+    /// 题号
+    pub question: u32,
+    /// 已填涂的选项序号
+    pub selected_options: Vec<u32>,
+    /// 得分
+    pub score: f64,
+    /// 存在填涂程度不明确的选项，需要人工复查
+    pub need_check: bool,
+}
+
+/// 单张答题卡的成绩
+#[derive(Clone, Debug, Serialize)]
+pub struct ScoreReport {
+    /// 考号，无法识别的数位以 `?` 表示；模板中没有考号区块时为 `None`
+    pub student_id: Option<String>,
+    /// 各题得分，顺序与答案一致
+    pub questions: Vec<QuestionScore>,
+    /// 总得分
+    pub score: f64,
+    /// 总分
+    pub max_score: f64,
+    /// 存在需要人工复查的题目或考号
+    pub need_check: bool,
+}
+
+/// 读取考号，每一位应当恰好有一个已填涂的数值
+fn get_student_id(marks: &[FieldMark]) -> (Option<String>, bool) {
+    let digit_count = marks
+        .iter()
+        .filter_map(|mark| match mark.field {
+            BubbleField::IdDigit { digit, .. } => Some(digit + 1),
+            _ => None,
+        })
+        .max();
+    let digit_count = match digit_count {
+        Some(digit_count) => digit_count,
+        None => return (None, false),
+    };
+
+    let mut need_check = false;
+    let student_id = (0..digit_count)
+        .map(|target_digit| {
+            let mut values = vec![];
+            for mark in marks.iter() {
+                if let BubbleField::IdDigit { digit, value } = mark.field {
+                    if digit == target_digit {
+                        match mark.state {
+                            MarkState::Filled => values.push(value),
+                            MarkState::Ambiguous => need_check = true,
+                            MarkState::Empty => {}
+                        }
+                    }
+                }
+            }
+            match values[..] {
+                [value] if value < 10 => char::from_digit(value, 10).unwrap_or('?'),
+                // 未填涂、填涂了多个数值，或模板中的数值超出 0 ~ 9
+                _ => {
+                    need_check = true;
+                    '?'
+                }
+            }
+        })
+        .collect();
+
+    (Some(student_id), need_check)
+}
+
+/// ### 根据答案为单张答题卡评分
+///
+/// **参数列表：**
+/// - `marks`: 由 `omr::marks::read_template_marks` 读取的选项框
+/// - `key`: 答案
+///
+/// 填涂程度不明确的选项不计入所选选项，并将该题标记为需要复查
+pub fn score_sheet(marks: &[FieldMark], key: &AnswerKey) -> ScoreReport {
+    let questions: Vec<QuestionScore> = key
+        .questions
+        .iter()
+        .map(|question_key| {
+            let mut selected_options = BTreeSet::new();
+            let mut need_check = false;
+            for mark in marks.iter() {
+                if let BubbleField::Question { question, option } = mark.field {
+                    if question == question_key.question {
+                        match mark.state {
+                            MarkState::Filled => {
+                                selected_options.insert(option);
+                            }
+                            MarkState::Ambiguous => need_check = true,
+                            MarkState::Empty => {}
+                        }
+                    }
+                }
+            }
+
+            QuestionScore {
+                question: question_key.question,
+                score: question_key.get_score(&selected_options),
+                selected_options: selected_options.into_iter().collect(),
+                need_check,
+            }
+        })
+        .collect();
+
+    let (student_id, student_id_need_check) = get_student_id(marks);
+
+    ScoreReport {
+        student_id,
+        score: questions.iter().map(|question| question.score).sum(),
+        max_score: key.get_max_score(),
+        need_check: student_id_need_check || questions.iter().any(|question| question.need_check),
+        questions,
+    }
+}
+
+/// 转义 CSV 字段
+fn escape_csv_field(field: &str) -> String {
+    if field.contains(|c| c == ',' || c == '"' || c == '\n' || c == '\r') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        String::from(field)
+    }
+}
+
+/// 将选项序号转换为字母，0 对应 `A`
+fn get_option_letters(options: &[u32]) -> String {
+    options
+        .iter()
+        .map(|option| char::from_u32('A' as u32 + option).unwrap_or('?'))
+        .collect()
+}
+
+/// ### 将成绩导出为 CSV
+///
+/// **参数列表：**
+/// - `writer`: 输出目标
+/// - `key`: 答案，用于生成表头
+/// - `reports`: 答题卡名称与成绩
+///
+/// 每张答题卡输出一行，包含考号、总得分以及每道题目所选选项与得分。
+/// 各题按题号与答案对应，成绩中缺少答案中的题目时返回 `Error::InvalidParameter`
+pub fn write_csv<W: Write>(
+    writer: &mut W,
+    key: &AnswerKey,
+    reports: &[(String, ScoreReport)],
+) -> Result<()> {
+    let mut header = vec![
+        String::from("sheet"),
+        String::from("student_id"),
+        String::from("score"),
+        String::from("max_score"),
+        String::from("need_check"),
+    ];
+    for question_key in key.questions.iter() {
+        header.push(format!("q{}", question_key.question));
+        header.push(format!("q{}_score", question_key.question));
+    }
+    writeln!(writer, "{}", header.join(","))?;
+
+    for (sheet, report) in reports.iter() {
+        let mut row = vec![
+            escape_csv_field(sheet),
+            escape_csv_field(report.student_id.as_deref().unwrap_or("")),
+            report.score.to_string(),
+            report.max_score.to_string(),
+            report.need_check.to_string(),
+        ];
+        for question_key in key.questions.iter() {
+            let question = report
+                .questions
+                .iter()
+                .find(|question| question.question == question_key.question)
+                .ok_or_else(|| {
+                    Error::InvalidParameter(format!(
+                        "report of {} has no score for question {}",
+                        sheet, question_key.question
+                    ))
+                })?;
+            row.push(get_option_letters(&question.selected_options));
+            row.push(question.score.to_string());
+        }
+        writeln!(writer, "{}", row.join(","))?;
+    }
+
+    Ok(())
+}
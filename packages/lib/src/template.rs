@@ -0,0 +1,413 @@
+use std::{fs, path::Path};
+
+use opencv::{
+    core::{self, Point2f, Rect, DECOMP_LU},
+    imgproc,
+    prelude::MatTraitConst,
+    types::VectorOfPoint2f,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    anchor::{self, AnchorDetector, Anchors},
+    error::{Error, Result},
+    transfer::TransformableMatrix,
+};
+
+/// 答题卡上的相对坐标，`x`、`y` 分别为相对于答题卡宽度、高度的比例，取值范围为 [0, 1]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RelativePoint {
+    pub x: f64,
+    pub y: f64,
+}
+impl RelativePoint {
+    fn is_inside(&self) -> bool {
+        (0.0..=1.0).contains(&self.x) && (0.0..=1.0).contains(&self.y)
+    }
+}
+
+/// 选项框尺寸，`width`、`height` 分别为相对于答题卡宽度、高度的比例
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RelativeSize {
+    pub width: f64,
+    pub height: f64,
+}
+
+/// 选项的排列方向
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OptionLayout {
+    /// 同一题的选项自左向右排列，题目自上而下排列
+    Horizontal,
+    /// 同一题的选项自上而下排列，题目自左向右排列
+    Vertical,
+}
+
+/// 题目区块，包含若干道连续编号、选项数目相同的题目
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct QuestionBlock {
+    /// 区块中第一道题目的题号
+    pub first_question: u32,
+    /// 区块中的题目数目
+    pub question_count: u32,
+    /// 每道题目的选项数目
+    pub option_count: u32,
+    /// 第一道题目第一个选项框的中心
+    pub origin: RelativePoint,
+    /// 相邻两道题目的间距，与排列方向垂直
+    pub question_spacing: f64,
+    /// 相邻两个选项的间距，与排列方向一致
+    pub option_spacing: f64,
+    /// 选项的排列方向
+    pub layout: OptionLayout,
+}
+impl QuestionBlock {
+    /// 第 `question_index` 道题目第 `option_index` 个选项框的中心
+    fn get_center(&self, question_index: u32, option_index: u32) -> RelativePoint {
+        let question_offset = question_index as f64 * self.question_spacing;
+        let option_offset = option_index as f64 * self.option_spacing;
+        match self.layout {
+            OptionLayout::Horizontal => RelativePoint {
+                x: self.origin.x + option_offset,
+                y: self.origin.y + question_offset,
+            },
+            OptionLayout::Vertical => RelativePoint {
+                x: self.origin.x + question_offset,
+                y: self.origin.y + option_offset,
+            },
+        }
+    }
+}
+
+/// 考号区块，每一列填涂一位数字，列内自上而下依次为 0 ~ `value_count - 1`
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct IdBlock {
+    /// 考号的位数，即列数
+    pub digit_count: u32,
+    /// 每一列的可选数值数目
+    #[serde(default = "IdBlock::default_value_count")]
+    pub value_count: u32,
+    /// 第一列数值 0 的选项框中心
+    pub origin: RelativePoint,
+    /// 相邻两列的水平间距
+    pub digit_spacing: f64,
+    /// 同一列中相邻两个数值的垂直间距
+    pub value_spacing: f64,
+}
+impl IdBlock {
+    fn default_value_count() -> u32 {
+        10
+    }
+
+    fn get_center(&self, digit_index: u32, value: u32) -> RelativePoint {
+        RelativePoint {
+            x: self.origin.x + digit_index as f64 * self.digit_spacing,
+            y: self.origin.y + value as f64 * self.value_spacing,
+        }
+    }
+}
+
+/// 模板中选项框的含义
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BubbleField {
+    /// 第 `question` 题的第 `option` 个选项，均从 0 开始计数的选项序号，题号与模板一致
+    Question { question: u32, option: u32 },
+    /// 考号第 `digit` 位的数值 `value`
+    IdDigit { digit: u32, value: u32 },
+}
+
+/// 投影到图像上的选项框
+#[derive(Clone, Debug)]
+pub struct BubbleRegion {
+    /// 选项框的含义
+    pub field: BubbleField,
+    /// 选项框在图像中的外接矩形
+    pub rect: Rect,
+}
+
+/// ### 答题卡模板
+///
+/// 以相对于答题卡尺寸的比例描述版面，使同一套程序能够处理不同尺寸、不同版面的答题卡
+///
+/// 用例
+/// ```rust,no_run
+/// # use oics::template::SheetTemplate;
+/// # use oics::transfer::TransformableMatrix;
+/// # use opencv::imgcodecs;
+///
+/// let template = SheetTemplate::load("exam.json").unwrap();
+/// let src = TransformableMatrix::new("01234.jpg", imgcodecs::IMREAD_COLOR).unwrap();
+/// for region in template.project_onto(&src).unwrap() {
+///     println!("{:?}: {:?}", region.field, region.rect);
+/// }
+/// ```
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SheetTemplate {
+    /// 模板名称
+    pub name: String,
+    /// 定位块中心，按左上、右上、右下、左下的顺序排列，可为空。
+    /// 不为空时选项框经由定位块围成的四边形投影到图像上，见 `project_with_anchors`
+    #[serde(default)]
+    pub anchors: Vec<RelativePoint>,
+    /// 在图像中查找定位块所用的检测器，定位块尺寸、间距与默认值不同时需要调整
+    #[serde(default)]
+    pub anchor_detector: AnchorDetector,
+    /// 选项框尺寸
+    pub bubble_size: RelativeSize,
+    /// 题目区块
+    #[serde(default)]
+    pub question_blocks: Vec<QuestionBlock>,
+    /// 考号区块
+    #[serde(default)]
+    pub id_block: Option<IdBlock>,
+}
+impl SheetTemplate {
+    /// 从 JSON 字符串解析模板并校验
+    pub fn from_json_str(json: &str) -> Result<Self> {
+        let template: Self = serde_json::from_str(json)?;
+        template.validate()?;
+
+        Ok(template)
+    }
+
+    /// 从 JSON 文件加载模板并校验
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let json = fs::read_to_string(path)?;
+        Self::from_json_str(&json)
+    }
+
+    /// 将模板序列化为 JSON 字符串
+    pub fn to_json_string(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// ### 校验模板
+    ///
+    /// 检查尺寸是否为正、所有选项框是否位于答题卡内、各题目区块的题号是否重叠，
+    /// 不合法时返回 `Error::InvalidConfig`
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |message: String| Err(Error::InvalidConfig(message));
+
+        if !(self.anchors.is_empty() || self.anchors.len() == 4) {
+            return invalid(format!(
+                "expected 0 or 4 anchors, found {}",
+                self.anchors.len()
+            ));
+        }
+        if let Some(anchor) = self.anchors.iter().find(|anchor| !anchor.is_inside()) {
+            return invalid(format!("anchor {:?} is outside the sheet", anchor));
+        }
+
+        let size = &self.bubble_size;
+        if !(size.width > 0.0 && size.width <= 1.0 && size.height > 0.0 && size.height <= 1.0) {
+            return invalid(format!("bubble size {:?} is out of range", size));
+        }
+
+        let mut question_ranges = vec![];
+        for block in self.question_blocks.iter() {
+            if block.question_count == 0 || block.option_count == 0 {
+                return invalid(format!(
+                    "question block starting at {} is empty",
+                    block.first_question
+                ));
+            }
+            let last_center = block.get_center(block.question_count - 1, block.option_count - 1);
+            if !block.origin.is_inside() || !last_center.is_inside() {
+                return invalid(format!(
+                    "question block starting at {} is outside the sheet",
+                    block.first_question
+                ));
+            }
+            // 区块内最后一题的题号
+            let last_question = match block.first_question.checked_add(block.question_count - 1) {
+                Some(last_question) => last_question,
+                None => {
+                    return invalid(format!(
+                        "question numbers of block starting at {} overflow",
+                        block.first_question
+                    ))
+                }
+            };
+            question_ranges.push((block.first_question, last_question));
+        }
+
+        // 各题目区块的题号不能重叠
+        question_ranges.sort();
+        for pair in question_ranges.windows(2) {
+            if pair[1].0 <= pair[0].1 {
+                return invalid(format!("question {} is defined more than once", pair[1].0));
+            }
+        }
+
+        if let Some(block) = &self.id_block {
+            if block.digit_count == 0 || block.value_count == 0 {
+                return invalid(String::from("id block is empty"));
+            }
+            let last_center = block.get_center(block.digit_count - 1, block.value_count - 1);
+            if !block.origin.is_inside() || !last_center.is_inside() {
+                return invalid(String::from("id block is outside the sheet"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 模板中所有选项框的含义及其中心
+    fn get_bubble_centers(&self) -> Vec<(BubbleField, RelativePoint)> {
+        let mut centers = vec![];
+        for block in self.question_blocks.iter() {
+            for question_index in 0..block.question_count {
+                for option_index in 0..block.option_count {
+                    centers.push((
+                        BubbleField::Question {
+                            question: block.first_question.saturating_add(question_index),
+                            option: option_index,
+                        },
+                        block.get_center(question_index, option_index),
+                    ));
+                }
+            }
+        }
+        if let Some(block) = &self.id_block {
+            for digit in 0..block.digit_count {
+                for value in 0..block.value_count {
+                    centers.push((
+                        BubbleField::IdDigit { digit, value },
+                        block.get_center(digit, value),
+                    ));
+                }
+            }
+        }
+
+        centers
+    }
+
+    /// ### 将模板中的选项框投影到指定尺寸的图像上
+    ///
+    /// 图像范围即答题卡范围，不使用模板中的定位块
+    ///
+    /// **参数列表：**
+    /// - `width`: 图像宽度
+    /// - `height`: 图像高度
+    ///
+    pub fn project(&self, width: i32, height: i32) -> Vec<BubbleRegion> {
+        let bubble_width = (self.bubble_size.width * width as f64).round().max(1.0) as i32;
+        let bubble_height = (self.bubble_size.height * height as f64).round().max(1.0) as i32;
+
+        self.get_bubble_centers()
+            .into_iter()
+            .map(|(field, center)| BubbleRegion {
+                field,
+                rect: Rect::new(
+                    (center.x * width as f64).round() as i32 - bubble_width / 2,
+                    (center.y * height as f64).round() as i32 - bubble_height / 2,
+                    bubble_width,
+                    bubble_height,
+                ),
+            })
+            .collect()
+    }
+
+    /// ### 经由定位块将模板中的选项框投影到图像上
+    ///
+    /// 以模板中的定位块与图像中检测到的定位块计算透视变换，
+    /// 选项框的四个顶点经变换后取外接矩形，适用于存在透视畸变、未经拉直的图像
+    ///
+    /// **参数列表：**
+    /// - `anchors`: 由 `anchor::find_anchors` 在图像中找到的定位块中心
+    ///
+    /// 模板未定义定位块时返回 `Error::InvalidConfig`
+    pub fn project_with_anchors(&self, anchors: &Anchors) -> Result<Vec<BubbleRegion>> {
+        if self.anchors.len() != 4 {
+            return Err(Error::InvalidConfig(String::from(
+                "template does not define 4 anchors",
+            )));
+        }
+
+        let to_point = |point: &RelativePoint| Point2f::new(point.x as f32, point.y as f32);
+        let template_points =
+            VectorOfPoint2f::from(self.anchors.iter().map(to_point).collect::<Vec<Point2f>>());
+        let image_points = VectorOfPoint2f::from(vec![
+            anchors.top_left,
+            anchors.top_right,
+            anchors.bottom_right,
+            anchors.bottom_left,
+        ]);
+        let homography =
+            imgproc::get_perspective_transform(&template_points, &image_points, DECOMP_LU)?;
+
+        // 依次变换每个选项框的左上、右上、右下、左下顶点
+        let centers = self.get_bubble_centers();
+        let (half_width, half_height) =
+            (self.bubble_size.width / 2.0, self.bubble_size.height / 2.0);
+        let corners = VectorOfPoint2f::from(
+            centers
+                .iter()
+                .flat_map(|(_, center)| {
+                    [
+                        (center.x - half_width, center.y - half_height),
+                        (center.x + half_width, center.y - half_height),
+                        (center.x + half_width, center.y + half_height),
+                        (center.x - half_width, center.y + half_height),
+                    ]
+                })
+                .map(|(x, y)| Point2f::new(x as f32, y as f32))
+                .collect::<Vec<Point2f>>(),
+        );
+        let mut image_corners = VectorOfPoint2f::new();
+        core::perspective_transform(&corners, &mut image_corners, &homography)?;
+        let image_corners = image_corners.to_vec();
+
+        Ok(centers
+            .into_iter()
+            .zip(image_corners.chunks(4))
+            .map(|((field, _), corners)| {
+                let left = corners.iter().map(|point| point.x).fold(f32::MAX, f32::min);
+                let right = corners.iter().map(|point| point.x).fold(f32::MIN, f32::max);
+                let top = corners.iter().map(|point| point.y).fold(f32::MAX, f32::min);
+                let bottom = corners.iter().map(|point| point.y).fold(f32::MIN, f32::max);
+                BubbleRegion {
+                    field,
+                    rect: Rect::new(
+                        left.round() as i32,
+                        top.round() as i32,
+                        ((right - left).round() as i32).max(1),
+                        ((bottom - top).round() as i32).max(1),
+                    ),
+                }
+            })
+            .collect())
+    }
+
+    /// ### 将模板中的选项框投影到已纠偏的图像上
+    ///
+    /// 模板定义了定位块且以 `anchor_detector` 在图像中找到全部四个定位块时经由定位块投影，
+    /// 否则以图像范围作为答题卡范围投影。超出图像范围的选项框会被裁剪至图像内
+    pub fn project_onto(&self, src: &TransformableMatrix) -> Result<Vec<BubbleRegion>> {
+        let size = src.get_mat().size()?;
+
+        let anchors = if self.anchors.is_empty() {
+            None
+        } else {
+            anchor::find_anchors(src, &self.anchor_detector)?
+        };
+        let regions = match anchors {
+            Some(anchors) => self.project_with_anchors(&anchors)?,
+            None => self.project(size.width, size.height),
+        };
+
+        Ok(regions
+            .into_iter()
+            .map(|region| {
+                let rect = region.rect;
+                let (left, top) = (rect.x.max(0), rect.y.max(0));
+                let right = (rect.x + rect.width).min(size.width);
+                let bottom = (rect.y + rect.height).min(size.height);
+                BubbleRegion {
+                    field: region.field,
+                    rect: Rect::new(left, top, (right - left).max(0), (bottom - top).max(0)),
+                }
+            })
+            .collect())
+    }
+}
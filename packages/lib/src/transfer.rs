@@ -1,16 +1,21 @@
 use std::collections::HashMap;
 
 use opencv::{
-    core::{Point2f, Scalar, Size2f, Size2i, CV_PI},
+    core::{Point2f, Scalar, Size2f, Size2i, CV_32F, CV_8UC1, CV_PI, DECOMP_LU},
     highgui, imgcodecs,
     imgproc::{self, get_rotation_matrix_2d, warp_affine},
+    photo,
     prelude::{Mat, MatTrait, MatTraitConst, MatTraitConstManual, MatTraitManual},
-    types::VectorOfi32,
+    types::{VectorOfPoint, VectorOfPoint2f, VectorOfVectorOfPoint, VectorOfi32, VectorOfu8},
 };
 
 use crate::{
     calculate,
-    types::{ImageFormat, RotateClipStrategy},
+    error::{self, Error, Result},
+    types::{
+        BinarizationStrategy, DenoiseStrategy, IlluminationStrategy, ImageFormat,
+        PaperCropStrategy, RotateClipStrategy,
+    },
 };
 
 pub struct TransformableMatrix {
@@ -34,14 +39,9 @@ impl TransformableMatrix {
         }
     }
 
-    /// 根据文件路径加载新的 Mat
-    pub fn load_mat(
-        self: &mut Self,
-        filename: &str,
-        flags: i32,
-    ) -> Result<&mut Self, opencv::Error> {
-        let image = imgcodecs::imread(filename, flags)?;
-        self.matrix = image;
+    /// 根据文件路径加载新的 Mat，文件不存在或无法解码时返回 `Error::UnreadableInput`
+    pub fn load_mat(self: &mut Self, filename: &str, flags: i32) -> Result<&mut Self> {
+        self.matrix = Self::new(filename, flags)?.matrix;
 
         Ok(self)
     }
@@ -58,15 +58,41 @@ impl TransformableMatrix {
         }
     }
 
-    pub fn new(filename: &str, flags: i32) -> Result<Self, opencv::Error> {
+    /// 根据文件路径创建，文件不存在或无法解码时返回 `Error::UnreadableInput`
+    pub fn new(filename: &str, flags: i32) -> Result<Self> {
+        // imread 读取失败时不会报错，而是返回空 Mat
         let matrix = imgcodecs::imread(filename, flags)?;
+        if error::check_not_empty(&matrix).is_err() {
+            return Err(Error::UnreadableInput(String::from(filename)));
+        }
+
+        Ok(Self { matrix })
+    }
+
+    /// 从编码后的图像数据创建，无法解码时返回 `Error::UnreadableInput`
+    pub fn from_bytes(buffer: &[u8], flags: i32) -> Result<Self> {
+        // imdecode 解码失败时不会报错，而是返回空 Mat
+        let matrix = imgcodecs::imdecode(&VectorOfu8::from(buffer.to_vec()), flags)?;
+        if error::check_not_empty(&matrix).is_err() {
+            return Err(Error::UnreadableInput(format!(
+                "in-memory buffer of {} bytes",
+                buffer.len()
+            )));
+        }
+
         Ok(Self { matrix })
     }
 
-    pub fn scale_self(self: &mut Self, scale: f64) -> Result<&mut Self, opencv::Error> {
+    /// 图像是否为空，即宽或高为 0
+    pub fn is_empty(self: &Self) -> bool {
+        error::check_not_empty(&self.matrix).is_err()
+    }
+
+    pub fn scale_self(self: &mut Self, scale: f64) -> Result<&mut Self> {
         if scale == 1.0 {
             return Ok(self);
         }
+        error::check_positive("scale", scale)?;
 
         let mut dst = Mat::default();
         let size = self.matrix.size()?;
@@ -90,11 +116,7 @@ impl TransformableMatrix {
         Ok(self)
     }
 
-    pub fn shrink_to(
-        self: &mut Self,
-        max_width: i32,
-        max_height: i32,
-    ) -> Result<&mut Self, opencv::Error> {
+    pub fn shrink_to(self: &mut Self, max_width: i32, max_height: i32) -> Result<&mut Self> {
         let matrix = &self.matrix;
 
         let original_size = matrix.size()?;
@@ -125,11 +147,7 @@ impl TransformableMatrix {
         }
     }
 
-    pub fn resize_self(
-        self: &mut Self,
-        width: i32,
-        height: i32,
-    ) -> Result<&mut Self, opencv::Error> {
+    pub fn resize_self(self: &mut Self, width: i32, height: i32) -> Result<&mut Self> {
         let mut dst = Mat::default();
         imgproc::resize(
             &self.matrix,
@@ -145,7 +163,7 @@ impl TransformableMatrix {
     }
 
     /// 利用 opencv::highgui 窗口展示图片
-    pub fn show(self: &Self, win_name: &str) -> Result<(), opencv::Error> {
+    pub fn show(self: &Self, win_name: &str) -> Result<()> {
         highgui::named_window(win_name, highgui::WINDOW_NORMAL)?;
         highgui::imshow(win_name, &self.matrix)?;
         Ok(())
@@ -153,8 +171,8 @@ impl TransformableMatrix {
 
     /// 获取 mat 字节数组
     #[allow(dead_code)]
-    pub fn get_bytes(self: &Self) -> Result<&[u8], opencv::Error> {
-        self.matrix.data_bytes()
+    pub fn get_bytes(self: &Self) -> Result<&[u8]> {
+        Ok(self.matrix.data_bytes()?)
     }
 
     /// 将图像自身输出到指定位置
@@ -164,18 +182,21 @@ impl TransformableMatrix {
         filename: &str,
         format: ImageFormat,
         quality: i32,
-    ) -> Result<bool, opencv::Error> {
-        let mat = &self.matrix;
+    ) -> Result<bool> {
+        let (_, quality_vec) = get_encode_params(format, quality);
 
-        let quality_vec = match format {
-            ImageFormat::JPEG => VectorOfi32::from(vec![imgcodecs::IMWRITE_JPEG_QUALITY, quality]),
-            ImageFormat::PNG => {
-                VectorOfi32::from(vec![imgcodecs::IMWRITE_PNG_COMPRESSION, quality])
-            }
-            ImageFormat::WEBP => VectorOfi32::from(vec![imgcodecs::IMWRITE_WEBP_QUALITY, quality]),
-        };
+        Ok(imgcodecs::imwrite(filename, &self.matrix, &quality_vec)?)
+    }
 
-        return imgcodecs::imwrite(filename, mat, &quality_vec);
+    /// 将图像自身编码为指定格式的字节数组，不写入文件
+    pub fn encode(self: &Self, format: ImageFormat, quality: i32) -> Result<Vec<u8>> {
+        error::check_not_empty(&self.matrix)?;
+
+        let (extension, quality_vec) = get_encode_params(format, quality);
+        let mut buffer = VectorOfu8::default();
+        imgcodecs::imencode(extension, &self.matrix, &mut buffer, &quality_vec)?;
+
+        Ok(buffer.to_vec())
     }
 
     pub fn clone(&self) -> Self {
@@ -209,7 +230,7 @@ impl TransformableMatrix {
         kernel_size: opencv::core::Size,
         anchor: opencv::core::Point,
         iterations: i32,
-    ) -> opencv::Result<Self> {
+    ) -> Result<Self> {
         let mat = &self.matrix;
         let mut dilated = Mat::default();
 
@@ -257,7 +278,7 @@ impl TransformableMatrix {
         kernel_size: opencv::core::Size,
         anchor: opencv::core::Point,
         iterations: i32,
-    ) -> opencv::Result<Self> {
+    ) -> Result<Self> {
         let mat = &self.matrix;
         let mut eroded = Mat::default();
 
@@ -274,15 +295,306 @@ impl TransformableMatrix {
 
         Ok(Self { matrix: eroded })
     }
+
+    /// 图像降噪处理
+    /// `strategy`: 降噪方式
+    ///
+    /// 适用于灰度图与彩色图
+    ///
+    /// 用例
+    /// ```rust
+    /// # use oics::transfer::TransformableMatrix;
+    /// # use oics::types::DenoiseStrategy;
+    /// # use opencv::imgcodecs;
+    ///
+    /// let mut src = TransformableMatrix::new("01234.jpg", imgcodecs::IMREAD_COLOR).unwrap();
+    /// src.denoise(DenoiseStrategy::MEDIAN { kernel_size: 3 }).unwrap();
+    /// ```
+    pub fn denoise(self: &mut Self, strategy: DenoiseStrategy) -> Result<&mut Self> {
+        let mut dst = Mat::default();
+        match strategy {
+            DenoiseStrategy::NONE => return Ok(self),
+            DenoiseStrategy::MEDIAN { kernel_size } => {
+                imgproc::median_blur(&self.matrix, &mut dst, kernel_size.max(3) | 1)?;
+            }
+            DenoiseStrategy::BILATERAL {
+                diameter,
+                sigma_color,
+                sigma_space,
+            } => {
+                imgproc::bilateral_filter(
+                    &self.matrix,
+                    &mut dst,
+                    diameter,
+                    sigma_color,
+                    sigma_space,
+                    opencv::core::BORDER_DEFAULT,
+                )?;
+            }
+            DenoiseStrategy::NLMEANS {
+                h,
+                template_window_size,
+                search_window_size,
+            } => {
+                if self.matrix.channels() == 1 {
+                    photo::fast_nl_means_denoising(
+                        &self.matrix,
+                        &mut dst,
+                        h,
+                        template_window_size,
+                        search_window_size,
+                    )?;
+                } else {
+                    photo::fast_nl_means_denoising_colored(
+                        &self.matrix,
+                        &mut dst,
+                        h,
+                        h,
+                        template_window_size,
+                        search_window_size,
+                    )?;
+                }
+            }
+        }
+        self.matrix = dst;
+
+        Ok(self)
+    }
+
+    /// 光照归一化
+    /// `strategy`: 光照归一化方式
+    ///
+    /// 估计纸张背景后以原图除以背景，使背景趋于均匀的白色，适用于灰度图与彩色图
+    ///
+    /// 用例
+    /// ```rust
+    /// # use oics::transfer::TransformableMatrix;
+    /// # use oics::types::IlluminationStrategy;
+    /// # use opencv::imgcodecs;
+    ///
+    /// let mut src = TransformableMatrix::new("01234.jpg", imgcodecs::IMREAD_COLOR).unwrap();
+    /// src.normalize_illumination(IlluminationStrategy::MORPHOLOGY { kernel_size: 31 })
+    ///     .unwrap();
+    /// ```
+    pub fn normalize_illumination(
+        self: &mut Self,
+        strategy: IlluminationStrategy,
+    ) -> Result<&mut Self> {
+        // 背景估计核的边长应为不小于 3 的奇数
+        let get_kernel_size = |size: i32| size.max(3) | 1;
+
+        let background = match strategy {
+            IlluminationStrategy::NONE => return Ok(self),
+            IlluminationStrategy::MORPHOLOGY { kernel_size } => {
+                let kernel_size = get_kernel_size(kernel_size);
+                let kernel = imgproc::get_structuring_element(
+                    imgproc::MORPH_ELLIPSE,
+                    Size2i::new(kernel_size, kernel_size),
+                    opencv::core::Point::new(-1, -1),
+                )?;
+                // 闭运算以周围较亮的纸张填充较暗的墨迹
+                let mut dst = Mat::default();
+                imgproc::morphology_ex(
+                    &self.matrix,
+                    &mut dst,
+                    imgproc::MORPH_CLOSE,
+                    &kernel,
+                    opencv::core::Point::new(-1, -1),
+                    1,
+                    opencv::core::BORDER_REPLICATE,
+                    imgproc::morphology_default_border_value()?,
+                )?;
+                dst
+            }
+            IlluminationStrategy::MEDIAN { kernel_size } => {
+                let mut dst = Mat::default();
+                imgproc::median_blur(&self.matrix, &mut dst, get_kernel_size(kernel_size))?;
+                dst
+            }
+        };
+
+        // 原图除以背景，背景处趋近于 255
+        let mut dst = Mat::default();
+        opencv::core::divide2(&self.matrix, &background, &mut dst, 255.0, -1)?;
+        self.matrix = dst;
+
+        Ok(self)
+    }
+
+    /// 查找纸张边界并裁剪至纸张区域
+    /// `strategy`: 纸张边界裁剪方式
+    ///
+    /// 以 OTSU 阈值分离明亮的纸张与较暗的背景，取面积最大的轮廓作为纸张边界。
+    /// 未找到纸张边界，或纸张已占满图像时不做处理，返回 `false`
+    ///
+    /// 用例
+    /// ```rust
+    /// # use oics::transfer::TransformableMatrix;
+    /// # use oics::types::PaperCropStrategy;
+    /// # use opencv::imgcodecs;
+    ///
+    /// let mut src = TransformableMatrix::new("01234.jpg", imgcodecs::IMREAD_COLOR).unwrap();
+    /// let cropped = src.crop_to_paper(PaperCropStrategy::STRAIGHTEN).unwrap();
+    /// ```
+    pub fn crop_to_paper(self: &mut Self, strategy: PaperCropStrategy) -> Result<bool> {
+        if strategy == PaperCropStrategy::NONE {
+            return Ok(false);
+        }
+
+        // 纸张区域为白色的二值图
+        let paper_mat = {
+            let gray_mat = if self.matrix.channels() == 1 {
+                self.matrix.clone()
+            } else {
+                let mut dst = Mat::default();
+                imgproc::cvt_color(&self.matrix, &mut dst, imgproc::COLOR_RGB2GRAY, 0)?;
+                dst
+            };
+            let mut blurred = Mat::default();
+            imgproc::gaussian_blur(
+                &gray_mat,
+                &mut blurred,
+                Size2i::new(5, 5),
+                0.0,
+                0.0,
+                opencv::core::BORDER_DEFAULT,
+            )?;
+            let mut dst = Mat::default();
+            imgproc::threshold(
+                &blurred,
+                &mut dst,
+                0.0,
+                255.0,
+                imgproc::THRESH_BINARY | imgproc::THRESH_OTSU,
+            )?;
+            dst
+        };
+
+        let mut contours = VectorOfVectorOfPoint::new();
+        imgproc::find_contours(
+            &paper_mat,
+            &mut contours,
+            imgproc::RETR_EXTERNAL,
+            imgproc::CHAIN_APPROX_SIMPLE,
+            opencv::core::Point::new(0, 0),
+        )?;
+
+        // 面积最大的轮廓即为纸张边界
+        let mut paper_contour = None;
+        let mut paper_area = 0.0;
+        for contour in contours.iter() {
+            let area = imgproc::contour_area(&contour, false)?;
+            if area > paper_area {
+                paper_area = area;
+                paper_contour = Some(contour);
+            }
+        }
+
+        let size = self.matrix.size()?;
+        let image_area = size.width as f64 * size.height as f64;
+        let paper_contour = match paper_contour {
+            // 纸张面积过小时认为未找到纸张
+            Some(contour) if paper_area >= image_area * 0.2 => contour,
+            _ => return Ok(false),
+        };
+
+        if strategy == PaperCropStrategy::STRAIGHTEN {
+            let mut polygon = VectorOfPoint::new();
+            imgproc::approx_poly_dp(
+                &paper_contour,
+                &mut polygon,
+                0.02 * imgproc::arc_length(&paper_contour, true)?,
+                true,
+            )?;
+
+            if polygon.len() == 4 && imgproc::is_contour_convex(&polygon)? {
+                // 按左上、右上、右下、左下的顺序排列四边形顶点
+                let points: Vec<Point2f> = polygon
+                    .iter()
+                    .map(|point| Point2f::new(point.x as f32, point.y as f32))
+                    .collect();
+                let find_point = |key: &dyn Fn(&Point2f) -> f32| {
+                    *points
+                        .iter()
+                        .min_by(|a, b| key(a).total_cmp(&key(b)))
+                        .unwrap()
+                };
+                let top_left = find_point(&|point| point.x + point.y);
+                let top_right = find_point(&|point| point.y - point.x);
+                let bottom_right = find_point(&|point| -(point.x + point.y));
+                let bottom_left = find_point(&|point| point.x - point.y);
+
+                let get_distance = |a: &Point2f, b: &Point2f| {
+                    ((a.x - b.x).powf(2.0) + (a.y - b.y).powf(2.0)).sqrt()
+                };
+                let width = get_distance(&top_left, &top_right)
+                    .max(get_distance(&bottom_left, &bottom_right));
+                let height = get_distance(&top_left, &bottom_left)
+                    .max(get_distance(&top_right, &bottom_right));
+
+                let src_points =
+                    VectorOfPoint2f::from(vec![top_left, top_right, bottom_right, bottom_left]);
+                let dst_points = VectorOfPoint2f::from(vec![
+                    Point2f::new(0.0, 0.0),
+                    Point2f::new(width, 0.0),
+                    Point2f::new(width, height),
+                    Point2f::new(0.0, height),
+                ]);
+                let homography =
+                    imgproc::get_perspective_transform(&src_points, &dst_points, DECOMP_LU)?;
+
+                let mut dst = Mat::default();
+                imgproc::warp_perspective(
+                    &self.matrix,
+                    &mut dst,
+                    &homography,
+                    Size2i::new(width.round() as i32, height.round() as i32),
+                    imgproc::INTER_LINEAR,
+                    opencv::core::BORDER_CONSTANT,
+                    Scalar::new(255.0, 255.0, 255.0, 0.0), // b g r
+                )?;
+                self.matrix = dst;
+
+                return Ok(true);
+            }
+        }
+
+        // 裁剪至纸张的外接矩形
+        let rect = imgproc::bounding_rect(&paper_contour)?;
+        if rect.width == size.width && rect.height == size.height {
+            return Ok(false);
+        }
+        self.matrix = Mat::roi(&self.matrix, rect)?.clone();
+
+        Ok(true)
+    }
 }
 
 unsafe impl Sync for TransformableMatrix {}
 
-/// 将RGB图片转换成灰度图
+/// 获取图像格式对应的扩展名与编码参数
+fn get_encode_params(format: ImageFormat, quality: i32) -> (&'static str, VectorOfi32) {
+    match format {
+        ImageFormat::JPEG => (
+            ".jpg",
+            VectorOfi32::from(vec![imgcodecs::IMWRITE_JPEG_QUALITY, quality]),
+        ),
+        ImageFormat::PNG => (
+            ".png",
+            VectorOfi32::from(vec![imgcodecs::IMWRITE_PNG_COMPRESSION, quality]),
+        ),
+        ImageFormat::WEBP => (
+            ".webp",
+            VectorOfi32::from(vec![imgcodecs::IMWRITE_WEBP_QUALITY, quality]),
+        ),
+    }
+}
+
+/// 将RGB图片转换成灰度图，图像为空时返回 `Error::EmptyImage`
 #[allow(dead_code)]
-pub fn transfer_rgb_image_to_gray_image(
-    src: &TransformableMatrix,
-) -> Result<TransformableMatrix, opencv::Error> {
+pub fn transfer_rgb_image_to_gray_image(src: &TransformableMatrix) -> Result<TransformableMatrix> {
+    error::check_not_empty(&src.matrix)?;
+
     let mut dst = Mat::default();
     imgproc::cvt_color(&src.matrix, &mut dst, imgproc::COLOR_RGB2GRAY, 0)?;
 
@@ -293,16 +605,183 @@ pub fn transfer_rgb_image_to_gray_image(
 #[allow(dead_code)]
 pub fn transfer_gray_image_to_thresh_binary(
     src: &TransformableMatrix,
-) -> Result<TransformableMatrix, opencv::Error> {
+) -> Result<TransformableMatrix> {
+    transfer_gray_image_to_thresh_binary_by_strategy(src, BinarizationStrategy::default())
+}
+
+/// 以指定的二值化方式将灰度图转换成黑白二值图
+pub fn transfer_gray_image_to_thresh_binary_by_strategy(
+    src: &TransformableMatrix,
+    strategy: BinarizationStrategy,
+) -> Result<TransformableMatrix> {
+    Ok(TransformableMatrix {
+        matrix: get_thresh_binary_mat(&src.matrix, strategy)?,
+    })
+}
+
+/// 检查二值化参数，不合法时返回 `Error::InvalidParameter`
+fn check_binarization_strategy(strategy: BinarizationStrategy) -> Result<()> {
+    // 自适应阈值的窗口边长应为不小于 3 的奇数
+    let check_window_size = |size: i32| {
+        if size < 3 || size % 2 == 0 {
+            return Err(Error::InvalidParameter(format!(
+                "binarization window size must be an odd number not less than 3, got {}",
+                size
+            )));
+        }
+
+        Ok(())
+    };
+    let check_finite = |name: &str, value: f64| {
+        if !value.is_finite() {
+            return Err(Error::InvalidParameter(format!(
+                "binarization {} must be finite, got {}",
+                name, value
+            )));
+        }
+
+        Ok(())
+    };
+
+    match strategy {
+        BinarizationStrategy::FIXED { threshold } => check_finite("threshold", threshold),
+        BinarizationStrategy::OTSU => Ok(()),
+        BinarizationStrategy::MEAN { block_size, c }
+        | BinarizationStrategy::GAUSSIAN { block_size, c } => {
+            check_window_size(block_size)?;
+            check_finite("c", c)
+        }
+        BinarizationStrategy::SAUVOLA { window_size, k, r } => {
+            check_window_size(window_size)?;
+            check_finite("k", k)?;
+            // r 为标准差的动态范围，作除数
+            check_finite("r", r)?;
+            error::check_positive("binarization r", r)
+        }
+        BinarizationStrategy::NIBLACK { window_size, k } => {
+            check_window_size(window_size)?;
+            check_finite("k", k)
+        }
+    }
+}
+
+/// ### 以指定的二值化方式将灰度图转换成黑白二值图
+///
+/// **参数列表：**
+/// - `src`: 单通道灰度图
+/// - `strategy`: 二值化方式
+///
+/// 输出图像中背景为白色（255），墨迹为黑色（0）。
+/// 窗口边长不是不小于 3 的奇数、参数不是有限数或 Sauvola 的 `r` 不为正数时返回 `Error::InvalidParameter`
+pub fn get_thresh_binary_mat(src: &Mat, strategy: BinarizationStrategy) -> Result<Mat> {
+    check_binarization_strategy(strategy)?;
+
     let mut dst = Mat::default();
-    imgproc::threshold(&src.matrix, &mut dst, 127.0, 255.0, imgproc::THRESH_BINARY)?;
+    match strategy {
+        BinarizationStrategy::FIXED { threshold } => {
+            imgproc::threshold(src, &mut dst, threshold, 255.0, imgproc::THRESH_BINARY)?;
+        }
+        BinarizationStrategy::OTSU => {
+            imgproc::threshold(
+                src,
+                &mut dst,
+                0.0,
+                255.0,
+                imgproc::THRESH_BINARY | imgproc::THRESH_OTSU,
+            )?;
+        }
+        BinarizationStrategy::MEAN { block_size, c } => {
+            imgproc::adaptive_threshold(
+                src,
+                &mut dst,
+                255.0,
+                imgproc::ADAPTIVE_THRESH_MEAN_C,
+                imgproc::THRESH_BINARY,
+                block_size,
+                c,
+            )?;
+        }
+        BinarizationStrategy::GAUSSIAN { block_size, c } => {
+            imgproc::adaptive_threshold(
+                src,
+                &mut dst,
+                255.0,
+                imgproc::ADAPTIVE_THRESH_GAUSSIAN_C,
+                imgproc::THRESH_BINARY,
+                block_size,
+                c,
+            )?;
+        }
+        BinarizationStrategy::SAUVOLA { window_size, k, r } => {
+            dst = get_local_thresh_binary_mat(src, window_size, |mean, sd| {
+                mean * (1.0 + k * (sd / r - 1.0))
+            })?;
+        }
+        BinarizationStrategy::NIBLACK { window_size, k } => {
+            dst = get_local_thresh_binary_mat(src, window_size, |mean, sd| mean + k * sd)?;
+        }
+    }
 
-    Ok(TransformableMatrix::from_matrix(&dst))
+    Ok(dst)
+}
+
+/// 根据窗口内灰度的均值与标准差逐像素计算阈值并二值化
+fn get_local_thresh_binary_mat(
+    src: &Mat,
+    window_size: i32,
+    get_threshold: impl Fn(f64, f64) -> f64,
+) -> Result<Mat> {
+    let mut src_f32 = Mat::default();
+    src.convert_to(&mut src_f32, CV_32F, 1.0, 0.0)?;
+
+    // 窗口内的灰度均值与灰度平方均值
+    let window = Size2i::new(window_size, window_size);
+    let mut mean_mat = Mat::default();
+    imgproc::box_filter(
+        &src_f32,
+        &mut mean_mat,
+        -1,
+        window,
+        opencv::core::Point::new(-1, -1),
+        true,
+        opencv::core::BORDER_REPLICATE,
+    )?;
+    let mut square_mean_mat = Mat::default();
+    imgproc::sqr_box_filter(
+        &src_f32,
+        &mut square_mean_mat,
+        -1,
+        window,
+        opencv::core::Point::new(-1, -1),
+        true,
+        opencv::core::BORDER_REPLICATE,
+    )?;
+
+    let mut dst =
+        Mat::new_rows_cols_with_default(src.rows(), src.cols(), CV_8UC1, Scalar::all(0.0))?;
+    for row_index in 0..src.rows() {
+        let src_row = src.at_row::<u8>(row_index)?;
+        let mean_row = mean_mat.at_row::<f32>(row_index)?;
+        let square_mean_row = square_mean_mat.at_row::<f32>(row_index)?;
+        let dst_row = dst.at_row_mut::<u8>(row_index)?;
+
+        for col_index in 0..src_row.len() {
+            let mean = mean_row[col_index] as f64;
+            let sd = (square_mean_row[col_index] as f64 - mean * mean)
+                .max(0.0)
+                .sqrt();
+            if src_row[col_index] as f64 > get_threshold(mean, sd) {
+                dst_row[col_index] = 255;
+            }
+        }
+    }
+
+    Ok(dst)
 }
 
 /// 提取黑白二值图的横向投影数据
 #[allow(dead_code)]
-pub fn get_horizontal_projection(src: &TransformableMatrix) -> Result<Vec<f64>, opencv::Error> {
+pub fn get_horizontal_projection(src: &TransformableMatrix) -> Result<Vec<f64>> {
     let mat = &src.matrix;
 
     let mut result: Vec<f64> = Vec::with_capacity(mat.rows() as usize);
@@ -336,7 +815,7 @@ pub fn get_horizontal_projection(src: &TransformableMatrix) -> Result<Vec<f64>,
 #[allow(dead_code)]
 pub fn transfer_thresh_binary_to_horizontal_projection(
     src: &TransformableMatrix,
-) -> Result<TransformableMatrix, opencv::Error> {
+) -> Result<TransformableMatrix> {
     // 克隆原图作为目标图片
     let mut mat = (&src.matrix).clone();
 
@@ -377,7 +856,7 @@ pub fn transfer_thresh_binary_to_horizontal_projection(
 
 /// 提取黑白二值图的纵向投影数据
 #[allow(dead_code)]
-pub fn get_vertical_projection(src: &TransformableMatrix) -> Result<Vec<f64>, opencv::Error> {
+pub fn get_vertical_projection(src: &TransformableMatrix) -> Result<Vec<f64>> {
     let mat: &Mat = &src.matrix;
 
     let mut result = vec![0.0; mat.cols() as usize];
@@ -408,7 +887,7 @@ pub fn get_vertical_projection(src: &TransformableMatrix) -> Result<Vec<f64>, op
 #[allow(dead_code)]
 pub fn transfer_thresh_binary_to_vertical_projection(
     src: &TransformableMatrix,
-) -> Result<TransformableMatrix, opencv::Error> {
+) -> Result<TransformableMatrix> {
     // 克隆原图作为目标图片
     let mut mat = (&src.matrix).clone();
 
@@ -455,6 +934,9 @@ pub fn transfer_thresh_binary_to_vertical_projection(
 }
 
 /// 旋转视图
+///
+/// `RotateClipStrategy::CONTAIN` 时以原图中心为旋转中心，并将其平移至扩展后的输出图像中心，
+/// 与 `omr::correct_matrix` 输出图像的旋转方式一致
 #[allow(dead_code)]
 pub fn rotate_mat(
     src: &TransformableMatrix,
@@ -464,7 +946,7 @@ pub fn rotate_mat(
     border_mode: i32,
     border_value: Scalar,
     clip_strategy: RotateClipStrategy,
-) -> Result<TransformableMatrix, opencv::Error> {
+) -> Result<TransformableMatrix> {
     let mat = &src.matrix;
     let mut dst = Mat::default();
 
@@ -493,18 +975,18 @@ pub fn rotate_mat(
                 + (mat.rows() as f64) * (angle * CV_PI / 180.0).cos().abs())
             .ceil();
 
-            // 计算仿射变换矩阵
+            // 计算仿射变换矩阵，以原图中心为旋转中心
             let center_point = Point2f::from_size(Size2f::new(
-                (rotated_width / 2.0).ceil() as f32,
-                (rotated_height / 2.0).ceil() as f32,
+                mat.cols() as f32 / 2.0,
+                mat.rows() as f32 / 2.0,
             ));
             let mut rotate_matrix = get_rotation_matrix_2d(center_point, angle, scale)?;
 
-            // 防止切边，对平移矩阵进行修改
+            // 防止切边，对平移矩阵进行修改，使旋转中心位于输出图像中心
             let element = rotate_matrix.at_2d_mut::<f64>(0, 2)?;
-            *element += ((rotated_width - mat.cols() as f64) / 2.0).ceil();
+            *element += (rotated_width - mat.cols() as f64) / 2.0;
             let element = rotate_matrix.at_2d_mut::<f64>(1, 2)?;
-            *element += ((rotated_height - mat.rows() as f64) / 2.0).ceil();
+            *element += (rotated_height - mat.rows() as f64) / 2.0;
 
             // 应用仿射变换
             warp_affine(
@@ -524,13 +1006,11 @@ pub fn rotate_mat(
 
 /// 获取投影曲线的垂直标准差和水平标准差
 #[allow(dead_code)]
-pub fn get_projection_standard_deviations(
-    src: &TransformableMatrix,
-) -> Result<(f64, f64), opencv::Error> {
+pub fn get_projection_standard_deviations(src: &TransformableMatrix) -> Result<(f64, f64)> {
     let vertical_projection = &self::get_vertical_projection(src)?;
-    let vertical_standard_deviation = calculate::get_standard_deviation(vertical_projection);
+    let vertical_standard_deviation = calculate::get_standard_deviation(vertical_projection)?;
     let horizontal_projection = &self::get_horizontal_projection(src)?;
-    let horizontal_standard_deviation = calculate::get_standard_deviation(horizontal_projection);
+    let horizontal_standard_deviation = calculate::get_standard_deviation(horizontal_projection)?;
 
     Ok((vertical_standard_deviation, horizontal_standard_deviation))
 }
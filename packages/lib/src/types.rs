@@ -1,11 +1,99 @@
+use serde::{Deserialize, Serialize};
+
 pub enum ImageFormat {
     JPEG,
     PNG,
     WEBP,
 }
 
+/// 二值化方式
+///
+/// 固定阈值无法处理光照不均、铅笔填涂较浅的扫描件，此时可选用全局自适应或局部自适应阈值
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum BinarizationStrategy {
+    /// 固定阈值，灰度大于阈值的像素为白色
+    FIXED { threshold: f64 },
+    /// OTSU 全局自适应阈值
+    OTSU,
+    /// 局部均值自适应阈值，`block_size` 为不小于 3 的奇数窗口边长，`c` 为从均值中减去的常数
+    MEAN { block_size: i32, c: f64 },
+    /// 局部高斯加权自适应阈值，`block_size` 为不小于 3 的奇数窗口边长，`c` 为从加权均值中减去的常数
+    GAUSSIAN { block_size: i32, c: f64 },
+    /// Sauvola 局部阈值 `m * (1 + k * (s / r - 1))`，`m`、`s` 分别为窗口内的均值与标准差，
+    /// `window_size` 为不小于 3 的奇数，`r` 为标准差的动态范围，应为正数
+    SAUVOLA { window_size: i32, k: f64, r: f64 },
+    /// Niblack 局部阈值 `m + k * s`，`m`、`s` 分别为窗口内的均值与标准差，
+    /// `window_size` 为不小于 3 的奇数
+    NIBLACK { window_size: i32, k: f64 },
+}
+impl Default for BinarizationStrategy {
+    fn default() -> Self {
+        Self::FIXED { threshold: 127.0 }
+    }
+}
+
+/// 光照归一化方式
+///
+/// 估计图像中纸张背景的亮度分布，再以原图除以背景，消除光照渐变与阴影
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum IlluminationStrategy {
+    /// 不进行光照归一化
+    NONE,
+    /// 以大尺寸核的形态学闭运算去除墨迹，估计背景
+    MORPHOLOGY { kernel_size: i32 },
+    /// 以大尺寸核的中值滤波去除墨迹，估计背景
+    MEDIAN { kernel_size: i32 },
+}
+
+/// 降噪方式
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum DenoiseStrategy {
+    /// 不进行降噪
+    NONE,
+    /// 中值滤波，适用于椒盐噪声，`kernel_size` 为奇数核边长
+    MEDIAN { kernel_size: i32 },
+    /// 双边滤波，在平滑高斯噪声的同时保留边缘
+    BILATERAL {
+        diameter: i32,
+        sigma_color: f64,
+        sigma_space: f64,
+    },
+    /// 非局部均值降噪，效果最好但耗时最长，`h` 为滤波强度
+    NLMEANS {
+        h: f32,
+        template_window_size: i32,
+        search_window_size: i32,
+    },
+}
+
+/// 纸张边界裁剪方式
+///
+/// 用于去除扫描图像中的扫描仪盖板、黑边、桌面等背景
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum PaperCropStrategy {
+    /// 不进行裁剪
+    NONE,
+    /// 裁剪至纸张的外接矩形
+    CROP,
+    /// 纸张边界为四边形时进行透视变换将其拉直，否则裁剪至外接矩形
+    STRAIGHTEN,
+}
+
 pub enum RotateClipStrategy {
     DEFAULT,
     CONTAIN,
     // COVER // TODO:
 }
+
+/// 峰值插值方式
+///
+/// 通过拟合峰值及其相邻两点的曲线，得到小于采样步长的峰值位置估计
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum PeakInterpolation {
+    /// 不进行插值
+    NONE,
+    /// 抛物线插值
+    PARABOLIC,
+    /// 高斯插值，要求峰值及其相邻两点均为正数
+    GAUSSIAN,
+}